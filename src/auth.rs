@@ -1,88 +1,549 @@
 use anyhow::{Context, Result, bail};
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use clap::Args;
+use log::{debug, info, warn};
 use reqwest::StatusCode;
-use serde::Deserialize;
-use std::time::Instant;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::time::{Duration as StdDuration, Instant};
 
 use crate::config::{self, Config};
+use crate::error::{ConfigError, TwitchError};
+use crate::{retry, twitch};
 
-#[derive(Debug, Args)]
+#[derive(Debug, Args, Default)]
 #[command(about = "Fetch a new Twitch app access token and update config")]
 pub struct AuthArgs {
     #[arg(long, help = "Print the updated configuration (secrets masked)")]
     pub show: bool,
-    #[arg(long, help = "Print verbose request and update details")]
-    pub verbose: bool,
+    #[arg(
+        long,
+        value_name = "TOKEN",
+        help = "Store an existing access token instead of requesting a new one"
+    )]
+    pub token: Option<String>,
+    #[arg(
+        long,
+        value_name = "SECS",
+        requires = "token",
+        conflicts_with = "expires_at",
+        help = "Lifetime of the token passed with --token, in seconds"
+    )]
+    pub expires_in: Option<i64>,
+    #[arg(
+        long,
+        value_name = "RFC3339",
+        requires = "token",
+        help = "Expiry of the token passed with --token as an RFC3339 timestamp"
+    )]
+    pub expires_at: Option<String>,
+    #[arg(
+        long,
+        requires = "token",
+        help = "Validate the token passed with --token against Twitch before storing it"
+    )]
+    pub validate: bool,
+    #[arg(
+        long,
+        conflicts_with = "token",
+        help = "Authorize as your Twitch user via the device code flow"
+    )]
+    pub user: bool,
+    #[arg(
+        long,
+        conflicts_with = "token",
+        help = "Revoke the stored access token on Twitch and remove it from config (the user token with --user)"
+    )]
+    pub revoke: bool,
+}
+
+const DEFAULT_AUTH_BASE: &str = "https://id.twitch.tv/oauth2";
+
+// Used when Twitch omits `expires_in` or reports a non-positive lifetime.
+const DEFAULT_TOKEN_LIFETIME_SECS: i64 = 3600;
+
+// Scopes requested for user tokens; `user:read:follows` is needed to read the
+// channels the user follows on Twitch.
+const USER_SCOPES: &str = FOLLOWS_SCOPE;
+
+pub(crate) const FOLLOWS_SCOPE: &str = "user:read:follows";
+
+#[derive(Debug, Deserialize)]
+struct ValidateResponse {
+    expires_in: i64,
+    #[serde(default)]
+    login: Option<String>,
+    #[serde(default)]
+    user_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeviceCodeResponse {
+    device_code: String,
+    expires_in: u64,
+    interval: u64,
+    user_code: String,
+    verification_uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct OAuthErrorResponse {
+    #[serde(default)]
+    message: String,
 }
 
 #[derive(Debug, Deserialize)]
 struct TokenResponse {
     access_token: String,
-    expires_in: i64,
+    expires_in: Option<i64>,
     #[allow(dead_code)]
     token_type: String,
+    // Only present for user tokens.
+    #[serde(default)]
+    refresh_token: Option<String>,
+    #[serde(default)]
+    scope: Vec<String>,
 }
 
 pub async fn run(args: AuthArgs) -> Result<()> {
+    if args.revoke {
+        return revoke(&args).await;
+    }
+    if let Some(token) = args.token.as_deref() {
+        return store_token(&args, token).await;
+    }
+    if args.user {
+        return authorize_user(&args).await;
+    }
+
     let mut config = config::load_config()?;
     let (client_id, client_secret) = credentials(&config)?;
 
-    let client = reqwest::Client::new();
+    let client = twitch::client(&config)?;
+    let token = request_app_token(client, &auth_url("token"), client_id, client_secret).await?;
+
+    let expires_in = token_lifetime(token.expires_in);
+    let expires_at = expiry_after(expires_in)?;
+    config.twitch.access_token = Some(token.access_token);
+    config.twitch.expires_at = Some(expires_at);
+
+    config::save_config_default(&config)?;
+    if let Ok(path) = config::config_path() {
+        info!("Updated config at {}", path.display());
+    }
+    info!("Token expires at {}", expires_at.to_rfc3339());
+    println!("Fetched new access token (expires in {}s).", expires_in);
+    if args.show {
+        config::print_config(&config)?;
+    }
+    Ok(())
+}
+
+// Client credentials grant against the token endpoint at `url`.
+async fn request_app_token(
+    client: &reqwest::Client,
+    url: &str,
+    client_id: &str,
+    client_secret: &str,
+) -> Result<TokenResponse> {
     let params = [
         ("client_id", client_id),
         ("client_secret", client_secret),
         ("grant_type", "client_credentials"),
     ];
-
-    if args.verbose {
-        eprintln!("[INFO] POST https://id.twitch.tv/oauth2/token");
-    }
+    debug!("POST {url}");
 
     let start = Instant::now();
-    let res = client
-        .post("https://id.twitch.tv/oauth2/token")
-        .form(&params)
-        .send()
+    let res = post_token(client, url, &params)
         .await
         .context("failed to send auth request to Twitch")?;
 
     let status = res.status();
-    if args.verbose {
-        eprintln!("[INFO] Response status: {}", status);
-        eprintln!("[INFO] Request duration: {}ms", start.elapsed().as_millis());
-    }
+    debug!("Response status: {}", status);
+    debug!("Request duration: {}ms", start.elapsed().as_millis());
     if !status.is_success() {
         let body = res.text().await.unwrap_or_default();
         return Err(map_auth_error(status, body));
     }
 
-    let token: TokenResponse = res
-        .json()
+    res.json()
         .await
-        .context("failed to parse Twitch token response")?;
+        .context("failed to parse Twitch token response")
+}
 
-    let expires_at = Utc::now() + Duration::seconds(token.expires_in);
-    config.twitch.access_token = Some(token.access_token);
-    config.twitch.expires_at = Some(expires_at);
+async fn store_token(args: &AuthArgs, token: &str) -> Result<()> {
+    let token = token.trim();
+    if token.is_empty() {
+        bail!("--token must not be empty.");
+    }
+
+    let mut config = config::load_config()?;
+    let validated = if args.validate {
+        Some(validate_token(&config, token).await?)
+    } else {
+        None
+    };
 
+    let expires_at = if let Some(secs) = args.expires_in {
+        if secs <= 0 {
+            bail!("--expires-in must be a positive number of seconds.");
+        }
+        expiry_after(secs).context("--expires-in is too large")?
+    } else if let Some(value) = args.expires_at.as_deref() {
+        DateTime::parse_from_rfc3339(value)
+            .with_context(|| "expires-at must be an RFC3339 timestamp")?
+            .with_timezone(&Utc)
+    } else if let Some(response) = validated {
+        expiry_after(token_lifetime(Some(response.expires_in)))?
+    } else {
+        bail!(
+            "--token requires --expires-in, --expires-at, or --validate to determine its expiry."
+        );
+    };
+
+    config.twitch.access_token = Some(token.to_string());
+    config.twitch.expires_at = Some(expires_at);
     config::save_config_default(&config)?;
-    if args.verbose {
-        if let Ok(path) = config::config_path() {
-            eprintln!("[INFO] Updated config at {}", path.display());
+
+    println!(
+        "Stored access token (expires at {}).",
+        expires_at.to_rfc3339()
+    );
+    if args.show {
+        config::print_config(&config)?;
+    }
+    Ok(())
+}
+
+async fn authorize_user(args: &AuthArgs) -> Result<()> {
+    let mut config = config::load_config()?;
+    let client_id = config::require_client_id(&config)?.to_string();
+
+    if config::user_token_needs_refresh(&config) && config.twitch.user_refresh_token.is_some() {
+        match refresh_user_token(&mut config).await {
+            Ok(expires_in) => {
+                println!("Refreshed user access token (expires in {}s).", expires_in);
+                if args.show {
+                    config::print_config(&config)?;
+                }
+                return Ok(());
+            }
+            Err(err) => warn!("{err:#}. Starting a new authorization."),
         }
-        eprintln!("[INFO] Token expires at {}", expires_at.to_rfc3339());
     }
+
+    let client = twitch::client(&config)?;
+
+    let device_url = auth_url("device");
+    debug!("POST {device_url}");
+    let res = client
+        .post(&device_url)
+        .form(&[("client_id", client_id.as_str()), ("scopes", USER_SCOPES)])
+        .send()
+        .await
+        .context("failed to request a device code from Twitch")?;
+    let status = res.status();
+    if !status.is_success() {
+        let body = res.text().await.unwrap_or_default();
+        return Err(map_auth_error(status, body));
+    }
+    let device: DeviceCodeResponse = res
+        .json()
+        .await
+        .context("failed to parse Twitch device code response")?;
+
     println!(
-        "Fetched new access token (expires in {}s).",
-        token.expires_in
+        "To authorize ttv, open {} and enter the code {}",
+        device.verification_uri, device.user_code
     );
+    println!("Waiting for authorization...");
+
+    let deadline = Instant::now() + StdDuration::from_secs(device.expires_in);
+    let mut interval = StdDuration::from_secs(device.interval.max(1));
+    let token_url = auth_url("token");
+    let token = loop {
+        tokio::time::sleep(interval).await;
+        if Instant::now() >= deadline {
+            bail!(
+                "The device code expired before authorization completed. Run `ttv auth --user` again."
+            );
+        }
+
+        let res = client
+            .post(&token_url)
+            .form(&[
+                ("client_id", client_id.as_str()),
+                ("scopes", USER_SCOPES),
+                ("device_code", device.device_code.as_str()),
+                ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
+            ])
+            .send()
+            .await
+            .context("failed to poll Twitch for the user token")?;
+
+        let status = res.status();
+        if status.is_success() {
+            break res
+                .json::<TokenResponse>()
+                .await
+                .context("failed to parse Twitch token response")?;
+        }
+
+        let body = res.text().await.unwrap_or_default();
+        let message = serde_json::from_str::<OAuthErrorResponse>(&body)
+            .map(|error| error.message)
+            .unwrap_or_default();
+        match message.as_str() {
+            "authorization_pending" => {}
+            "slow_down" => interval += StdDuration::from_secs(5),
+            "access_denied" => bail!("Authorization was denied on Twitch."),
+            "invalid device code" | "expired_token" => {
+                bail!(
+                    "The device code expired before authorization completed. Run `ttv auth --user` again."
+                )
+            }
+            _ => return Err(map_auth_error(status, body)),
+        }
+    };
+
+    let validated = validate_token(&config, &token.access_token).await?;
+    let expires_in = token_lifetime(token.expires_in);
+    let expires_at = expiry_after(expires_in)?;
+    config.twitch.user_access_token = Some(token.access_token);
+    config.twitch.user_refresh_token = token.refresh_token;
+    config.twitch.user_expires_at = Some(expires_at);
+    config.twitch.user_scopes = token.scope;
+    config.twitch.user_id = validated.user_id;
+    config.twitch.user_login = validated.login;
+    config::save_config_default(&config)?;
+
+    match config.twitch.user_login.as_deref() {
+        Some(login) => println!("Authorized as {} (expires in {}s).", login, expires_in),
+        None => println!("Authorized user token (expires in {}s).", expires_in),
+    }
     if args.show {
         config::print_config(&config)?;
     }
     Ok(())
 }
 
+async fn revoke(args: &AuthArgs) -> Result<()> {
+    let mut config = config::load_config()?;
+    let (kind, token) = if args.user {
+        ("user access token", config.twitch.user_access_token.clone())
+    } else {
+        ("access token", config.twitch.access_token.clone())
+    };
+    let Some(token) = token.filter(|value| !value.trim().is_empty()) else {
+        println!("No {kind} stored.");
+        return Ok(());
+    };
+    let client_id = config::require_client_id(&config)?.to_string();
+
+    let url = auth_url("revoke");
+    debug!("POST {url}");
+    let params = [("client_id", client_id.as_str()), ("token", token.trim())];
+    let res = post_token(twitch::client(&config)?, &url, &params)
+        .await
+        .context("failed to send token revocation request to Twitch")?;
+
+    let status = res.status();
+    debug!("Response status: {}", status);
+    // Twitch answers 400 `Invalid token` for tokens that already expired or
+    // were revoked; those are removed all the same.
+    let already_invalid = if status.is_success() {
+        false
+    } else {
+        let body = res.text().await.unwrap_or_default();
+        let message = serde_json::from_str::<OAuthErrorResponse>(&body)
+            .map(|error| error.message)
+            .unwrap_or_default();
+        // The revoke endpoint reports an unknown client ID as 404.
+        if status == StatusCode::NOT_FOUND {
+            return Err(TwitchError::error(
+                status,
+                "Invalid Twitch client ID. Double-check `ttv config --client-id`.",
+            ));
+        }
+        if status != StatusCode::BAD_REQUEST || !message.eq_ignore_ascii_case("invalid token") {
+            return Err(map_auth_error(status, body));
+        }
+        true
+    };
+
+    if args.user {
+        config.twitch.user_access_token = None;
+        config.twitch.user_refresh_token = None;
+        config.twitch.user_expires_at = None;
+        config.twitch.user_scopes.clear();
+        config.twitch.user_id = None;
+        config.twitch.user_login = None;
+    } else {
+        config.twitch.access_token = None;
+        config.twitch.expires_at = None;
+    }
+    config::save_config_default(&config)?;
+
+    if already_invalid {
+        println!("The stored {kind} was already invalid; removed it from config.");
+    } else {
+        println!("Revoked the stored {kind} and removed it from config.");
+    }
+    if args.show {
+        config::print_config(&config)?;
+    }
+    Ok(())
+}
+
+/// Exchanges the stored user refresh token for a new user access token and
+/// saves it. Returns the new token lifetime in seconds.
+pub(crate) async fn refresh_user_token(config: &mut Config) -> Result<i64> {
+    let client_id = config::require_client_id(config)?.to_string();
+    let refresh_token = config
+        .twitch
+        .user_refresh_token
+        .clone()
+        .filter(|value| !value.trim().is_empty())
+        .ok_or_else(|| {
+            ConfigError::error("No user refresh token stored. Run `ttv auth --user`.")
+        })?;
+
+    let mut params = vec![
+        ("client_id", client_id),
+        ("grant_type", "refresh_token".to_string()),
+        ("refresh_token", refresh_token),
+    ];
+    if let Some(secret) = config
+        .twitch
+        .client_secret
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+    {
+        params.push(("client_secret", secret.to_string()));
+    }
+
+    let url = auth_url("token");
+    debug!("POST {url} (refresh_token)");
+    let res = post_token(twitch::client(config)?, &url, &params)
+        .await
+        .context("failed to send token refresh request to Twitch")?;
+
+    let status = res.status();
+    debug!("Response status: {}", status);
+    if status == StatusCode::BAD_REQUEST || status == StatusCode::UNAUTHORIZED {
+        bail!("The stored user refresh token is invalid or revoked");
+    }
+    if !status.is_success() {
+        let body = res.text().await.unwrap_or_default();
+        return Err(map_auth_error(status, body));
+    }
+
+    let token: TokenResponse = res
+        .json()
+        .await
+        .context("failed to parse Twitch token response")?;
+
+    let expires_in = token_lifetime(token.expires_in);
+    config.twitch.user_access_token = Some(token.access_token);
+    config.twitch.user_expires_at = Some(expiry_after(expires_in)?);
+    if token.refresh_token.is_some() {
+        config.twitch.user_refresh_token = token.refresh_token;
+    }
+    if !token.scope.is_empty() {
+        config.twitch.user_scopes = token.scope;
+    }
+    config::save_config_default(config)?;
+    Ok(expires_in)
+}
+
+// Connection failures, timeouts, 429 and 5xx responses are retried with the
+// shared backoff settings. Other statuses are returned as-is so callers can
+// map them, and the last response is returned once retries run out.
+async fn post_token<P>(
+    client: &reqwest::Client,
+    url: &str,
+    params: &P,
+) -> reqwest::Result<reqwest::Response>
+where
+    P: Serialize + ?Sized,
+{
+    let retry = retry::config();
+    let mut attempt = 1;
+    loop {
+        match client.post(url).form(params).send().await {
+            Ok(res) if retry::is_retryable_status(res.status()) && retry.can_retry(attempt) => {
+                debug!("Token request returned {}, retrying", res.status());
+            }
+            Err(err) if retry::is_retryable_error(&err) && retry.can_retry(attempt) => {
+                debug!("Token request failed: {err}, retrying");
+            }
+            result => return result,
+        }
+        tokio::time::sleep(retry.delay_for(attempt)).await;
+        attempt += 1;
+    }
+}
+
+// `TTV_TWITCH_AUTH_BASE` replaces `https://id.twitch.tv/oauth2`, e.g. to
+// authenticate against a local mock.
+fn auth_url(path: &str) -> String {
+    let base = env::var("TTV_TWITCH_AUTH_BASE")
+        .ok()
+        .map(|value| value.trim().trim_end_matches('/').to_string())
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| DEFAULT_AUTH_BASE.to_string());
+    format!("{base}/{path}")
+}
+
+async fn validate_token(config: &Config, token: &str) -> Result<ValidateResponse> {
+    let url = auth_url("validate");
+    debug!("GET {url}");
+
+    let res = twitch::client(config)?
+        .get(&url)
+        .header(reqwest::header::AUTHORIZATION, format!("OAuth {}", token))
+        .send()
+        .await
+        .context("failed to send token validation request to Twitch")?;
+
+    let status = res.status();
+    debug!("Response status: {}", status);
+    if status == StatusCode::UNAUTHORIZED {
+        bail!("Twitch rejected the token as invalid or expired.");
+    }
+    if !status.is_success() {
+        let body = res.text().await.unwrap_or_default();
+        return Err(map_auth_error(status, body));
+    }
+
+    res.json()
+        .await
+        .context("failed to parse Twitch token validation response")
+}
+
+fn token_lifetime(expires_in: Option<i64>) -> i64 {
+    match expires_in {
+        Some(secs) if secs > 0 => secs,
+        Some(secs) => {
+            warn!(
+                "Twitch reported a token lifetime of {}s; assuming {}s instead.",
+                secs, DEFAULT_TOKEN_LIFETIME_SECS
+            );
+            DEFAULT_TOKEN_LIFETIME_SECS
+        }
+        None => DEFAULT_TOKEN_LIFETIME_SECS,
+    }
+}
+
+// `secs` from now; fails instead of panicking when the lifetime does not fit
+// in a timestamp.
+fn expiry_after(secs: i64) -> Result<DateTime<Utc>> {
+    Duration::try_seconds(secs)
+        .and_then(|lifetime| Utc::now().checked_add_signed(lifetime))
+        .with_context(|| format!("token lifetime of {}s is out of range", secs))
+}
+
 fn credentials(config: &Config) -> Result<(&str, &str)> {
     let mut missing = Vec::new();
 
@@ -107,10 +568,10 @@ fn credentials(config: &Config) -> Result<(&str, &str)> {
     }
 
     if !missing.is_empty() {
-        bail!(
+        return Err(ConfigError::error(format!(
             "Missing Twitch {}. Run `ttv config --client-id <ID> --client-secret <SECRET>` first.",
             missing.join(" and ")
-        );
+        )));
     }
 
     Ok((client_id.unwrap(), client_secret.unwrap()))
@@ -118,15 +579,93 @@ fn credentials(config: &Config) -> Result<(&str, &str)> {
 
 fn map_auth_error(status: StatusCode, body: String) -> anyhow::Error {
     match status {
-        StatusCode::BAD_REQUEST => {
-            anyhow::anyhow!("Invalid Twitch client ID. Double-check `ttv config --client-id`.")
-        }
-        StatusCode::FORBIDDEN => anyhow::anyhow!(
-            "Invalid Twitch client secret. Double-check `ttv config --client-secret`."
+        StatusCode::BAD_REQUEST => TwitchError::error(
+            status,
+            "Invalid Twitch client ID. Double-check `ttv config --client-id`.",
+        ),
+        StatusCode::FORBIDDEN => TwitchError::error(
+            status,
+            "Invalid Twitch client secret. Double-check `ttv config --client-secret`.",
         ),
         StatusCode::TOO_MANY_REQUESTS => {
-            anyhow::anyhow!("Twitch API rate limit exceeded. Try again later.")
+            TwitchError::error(status, "Twitch API rate limit exceeded. Try again later.")
         }
-        _ => anyhow::anyhow!("Unexpected Twitch auth response ({}). {}", status, body),
+        _ => TwitchError::error(
+            status,
+            format!("Unexpected Twitch auth response ({}). {}", status, body),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_string_contains, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn missing_expires_in_uses_default_lifetime() {
+        let token: TokenResponse =
+            serde_json::from_str(r#"{"access_token":"abc","token_type":"bearer"}"#).unwrap();
+        assert_eq!(token.expires_in, None);
+        assert_eq!(
+            token_lifetime(token.expires_in),
+            DEFAULT_TOKEN_LIFETIME_SECS
+        );
+    }
+
+    #[test]
+    fn non_positive_expires_in_uses_default_lifetime() {
+        assert_eq!(token_lifetime(Some(0)), DEFAULT_TOKEN_LIFETIME_SECS);
+        assert_eq!(token_lifetime(Some(-5)), DEFAULT_TOKEN_LIFETIME_SECS);
+        assert_eq!(token_lifetime(Some(120)), 120);
+    }
+
+    #[test]
+    fn expiry_after_rejects_out_of_range_lifetimes() {
+        assert!(expiry_after(3600).is_ok());
+        assert!(expiry_after(i64::MAX).is_err());
+    }
+
+    #[tokio::test]
+    async fn request_app_token_parses_the_token() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .and(body_string_contains("grant_type=client_credentials"))
+            .and(body_string_contains("client_id=id"))
+            .and(body_string_contains("client_secret=secret"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "abc",
+                "expires_in": 5000,
+                "token_type": "bearer"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let url = format!("{}/token", server.uri());
+        let token = request_app_token(&reqwest::Client::new(), &url, "id", "secret")
+            .await
+            .unwrap();
+        assert_eq!(token.access_token, "abc");
+        assert_eq!(token.expires_in, Some(5000));
+    }
+
+    #[tokio::test]
+    async fn request_app_token_maps_a_rejected_secret() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .respond_with(ResponseTemplate::new(403))
+            .mount(&server)
+            .await;
+
+        let url = format!("{}/token", server.uri());
+        let err = request_app_token(&reqwest::Client::new(), &url, "id", "secret")
+            .await
+            .unwrap_err();
+        let status = err.downcast_ref::<TwitchError>().map(|err| err.status);
+        assert_eq!(status, Some(StatusCode::FORBIDDEN));
     }
 }
@@ -0,0 +1,102 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use sqlx::SqlitePool;
+
+use crate::error::UserLookupError;
+use crate::twitch::{TwitchClient, TwitchGame, TwitchUser};
+use crate::{config, db, login};
+
+pub(crate) const DEFAULT_USER_TTL_SECS: u64 = 24 * 60 * 60;
+// Game IDs practically never change, so names are only re-resolved monthly.
+const GAME_TTL_SECS: i64 = 30 * 24 * 60 * 60;
+
+/// Resolves logins through the local user cache and only asks Twitch for the
+/// ones without an entry younger than the configured TTL. A TTL of 0 always
+/// asks Twitch. Malformed logins are skipped rather than sent, since Twitch
+/// rejects the whole batch for them; like unknown logins, they are simply
+/// missing from the result.
+pub async fn users_by_login(
+    pool: &SqlitePool,
+    config: &config::Config,
+    twitch: &TwitchClient,
+    logins: &[String],
+) -> Result<Vec<TwitchUser>> {
+    let ttl = config.cache.user_ttl_secs.unwrap_or(DEFAULT_USER_TTL_SECS);
+    let now = Utc::now();
+    let mut users = if ttl == 0 {
+        Vec::new()
+    } else {
+        // A TTL too large to subtract from now keeps every entry fresh.
+        let refreshed_since = i64::try_from(ttl)
+            .ok()
+            .and_then(Duration::try_seconds)
+            .and_then(|ttl| now.checked_sub_signed(ttl))
+            .unwrap_or(DateTime::UNIX_EPOCH)
+            .to_rfc3339_opts(SecondsFormat::Secs, true);
+        db::cached_users_by_login(pool, logins, &refreshed_since).await?
+    };
+
+    let cached: HashSet<String> = users.iter().map(|user| user.login.to_lowercase()).collect();
+    let missing: Vec<String> = logins
+        .iter()
+        .filter(|login| login::is_valid_login(login) && !cached.contains(&login.to_lowercase()))
+        .cloned()
+        .collect();
+    if missing.is_empty() {
+        return Ok(users);
+    }
+
+    let fetched = twitch.fetch_users_by_login(&missing).await?;
+    let refreshed_at = now.to_rfc3339_opts(SecondsFormat::Secs, true);
+    db::cache_users(pool, &fetched, &refreshed_at).await?;
+    users.extend(fetched);
+    Ok(users)
+}
+
+pub async fn user_by_login(
+    pool: &SqlitePool,
+    config: &config::Config,
+    twitch: &TwitchClient,
+    login: &str,
+) -> Result<TwitchUser> {
+    if !login::is_valid_login(login) {
+        return Err(UserLookupError::InvalidLogin(login.to_string()).into());
+    }
+    let users = users_by_login(pool, config, twitch, &[login.to_string()]).await?;
+    if let Some(user) = users.into_iter().next() {
+        return Ok(user);
+    }
+    if db::is_followed(pool, login).await? {
+        Err(UserLookupError::Unavailable(login.to_string()).into())
+    } else {
+        Err(UserLookupError::NotFound(login.to_string()).into())
+    }
+}
+
+/// Resolves a game name through the local game cache. With `refresh`, or
+/// when the entry is older than a month, Twitch is asked again. Names Twitch
+/// does not know are not cached.
+pub async fn game_by_name(
+    pool: &SqlitePool,
+    twitch: &TwitchClient,
+    name: &str,
+    refresh: bool,
+) -> Result<Option<TwitchGame>> {
+    let now = Utc::now();
+    if !refresh {
+        let refreshed_since =
+            (now - Duration::seconds(GAME_TTL_SECS)).to_rfc3339_opts(SecondsFormat::Secs, true);
+        if let Some(game) = db::cached_game(pool, name, &refreshed_since).await? {
+            return Ok(Some(game));
+        }
+    }
+
+    let Some(game) = twitch.fetch_game_by_name(name).await? else {
+        return Ok(None);
+    };
+    let refreshed_at = now.to_rfc3339_opts(SecondsFormat::Secs, true);
+    db::cache_game(pool, name, &game, &refreshed_at).await?;
+    Ok(Some(game))
+}
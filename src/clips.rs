@@ -0,0 +1,78 @@
+use anyhow::Result;
+use clap::Args;
+
+use crate::twitch::TwitchClient;
+use crate::{auth, cache, config, db, prompt, streamlink, template};
+
+#[derive(Debug, Args)]
+#[command(about = "Watch clips of a Twitch streamer")]
+pub struct ClipsArgs {
+    #[arg(value_name = "LOGIN", help = "Twitch login name")]
+    pub login: String,
+    #[arg(
+        long,
+        default_value_t = 20,
+        value_parser = clap::value_parser!(u8).range(1..=100),
+        help = "Number of clips to list (1-100)"
+    )]
+    pub first: u8,
+    #[arg(
+        long,
+        value_parser = streamlink::parse_quality,
+        help = "Clip quality or fallback list like 1080,720,best (defaults to config default_quality, then best)"
+    )]
+    pub quality: Option<String>,
+    #[arg(long, help = "Player executable (defaults to config player, then mpv)")]
+    pub player: Option<String>,
+}
+
+pub async fn run(args: ClipsArgs) -> Result<()> {
+    let mut config = config::load_config()?;
+    let mut options = streamlink::LaunchOptions {
+        quality: args.quality.or(config.player.default_quality.clone()),
+        player: args.player.or(config.player.player.clone()),
+        player_args: config.player.player_args.clone(),
+        extra_args: config.player.streamlink_args.clone(),
+        ..Default::default()
+    };
+    streamlink::ensure_dependencies(&options)?;
+
+    if config::token_needs_refresh(&config) {
+        auth::run(auth::AuthArgs::default()).await?;
+        config = config::load_config()?;
+    }
+
+    let client = TwitchClient::from_config(&config)?;
+
+    let pool = db::connect().await?;
+    let user = cache::user_by_login(&pool, &config, &client, &args.login).await?;
+    let clips = client.fetch_clips_by_user_id(&user.id, args.first).await?;
+
+    if clips.is_empty() {
+        println!("No clips found for {}.", user.display_name);
+        return Ok(());
+    }
+
+    println!("Clips for {}:", user.display_name);
+    for (idx, clip) in clips.iter().enumerate() {
+        println!(
+            "{:>2}) [{}] {} ({} views)",
+            idx + 1,
+            clip.created_at,
+            clip.title,
+            clip.view_count
+        );
+    }
+
+    let selection = prompt::select("clip", clips.len())?;
+    let clip = &clips[selection - 1];
+    println!("Starting clip {}...", clip.title);
+    options.title = Some(template::render(
+        streamlink::VIDEO_TITLE_TEMPLATE,
+        &[("display_name", &user.display_name), ("title", &clip.title)],
+    ));
+
+    streamlink::launch(&clip.url, &options).await?;
+
+    Ok(())
+}
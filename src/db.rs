@@ -1,35 +1,201 @@
-use std::path::PathBuf;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
 
-use anyhow::{Context, Result};
-use sqlx::sqlite::SqliteConnectOptions;
-use sqlx::{Row, SqlitePool};
+use anyhow::{Context, Result, bail};
+use clap::{Args, Subcommand};
+use serde::Serialize;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteConnection};
+use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool};
 
-use crate::{fs_utils, paths, twitch::TwitchUser};
+use crate::retry::{self, RetryConfig};
+use crate::twitch::{TwitchGame, TwitchUser};
+use crate::{fs_utils, paths};
 
-const DB_FILENAME: &str = "ttv.sqlite";
+// Bound parameters per statement; SQLite builds before 3.32 allow 999.
+const MAX_BIND_PARAMS: usize = 900;
+
+#[derive(Debug, Args)]
+#[command(about = "Maintain the local follow database")]
+pub struct DbArgs {
+    #[command(subcommand)]
+    pub command: DbCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum DbCommand {
+    #[command(about = "Check the database file for corruption")]
+    Check,
+    #[command(about = "Compact the database file and refresh query statistics")]
+    Vacuum,
+    #[command(about = "Apply pending schema migrations")]
+    Migrate(MigrateArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct MigrateArgs {
+    #[arg(long, help = "Only list pending migrations without applying them")]
+    pub dry_run: bool,
+}
+
+pub async fn run(args: DbArgs) -> Result<()> {
+    match args.command {
+        DbCommand::Check => check().await,
+        DbCommand::Vacuum => vacuum().await,
+        DbCommand::Migrate(args) => migrate(args).await,
+    }
+}
+
+async fn check() -> Result<()> {
+    // A damaged database must not be migrated before it has been checked.
+    let pool = open().await?;
+    let mut problems = integrity_check(&pool).await?;
+    problems.extend(foreign_key_check(&pool).await?);
+
+    let path = db_path()?;
+    if problems.is_empty() {
+        println!("Database at {} is ok.", path.display());
+        return Ok(());
+    }
+
+    for problem in &problems {
+        eprintln!("{}", problem);
+    }
+    bail!(
+        "Database at {} is corrupted. Restore it from a backup, or move it aside and follow your streamers again.",
+        path.display()
+    )
+}
+
+async fn vacuum() -> Result<()> {
+    let pool = connect().await?;
+    let path = db_path()?;
+    let before = file_size(&path)?;
+
+    sqlx::query("VACUUM")
+        .execute(&pool)
+        .await
+        .context("failed to vacuum database")?;
+    sqlx::query("PRAGMA optimize")
+        .execute(&pool)
+        .await
+        .context("failed to optimize database")?;
+
+    let after = file_size(&path)?;
+    println!(
+        "Vacuumed {}: {} -> {}.",
+        path.display(),
+        format_size(before),
+        format_size(after)
+    );
+    Ok(())
+}
+
+async fn migrate(args: MigrateArgs) -> Result<()> {
+    let pool = open().await?;
+    let current = schema_version(&pool).await?;
+    let pending: Vec<&Migration> = pending_migrations(current).collect();
+    println!("Schema version: {}", current);
+    if pending.is_empty() {
+        println!("No pending migrations.");
+        return Ok(());
+    }
+
+    if args.dry_run {
+        println!("{} pending migration(s):", pending.len());
+        for migration in &pending {
+            println!("  {}: {}", migration.version, migration.description);
+        }
+        return Ok(());
+    }
+
+    init_schema(&pool).await?;
+    for migration in &pending {
+        println!("Applied {}: {}", migration.version, migration.description);
+    }
+    Ok(())
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 3] = ["KiB", "MiB", "GiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+fn file_size(path: &Path) -> Result<u64> {
+    let metadata =
+        fs::metadata(path).with_context(|| format!("failed to read {}", path.display()))?;
+    Ok(metadata.len())
+}
 
 pub async fn connect() -> Result<SqlitePool> {
+    let pool = open().await?;
+    init_schema(&pool).await?;
+    Ok(pool)
+}
+
+// Opens the database without applying migrations.
+async fn open() -> Result<SqlitePool> {
     let path = db_path()?;
     let dir = path
         .parent()
         .context("database path should have a parent directory")?;
-    fs_utils::ensure_dir(dir)?;
+    // An overridden path may point into a shared directory such as /tmp, so
+    // only directories that do not exist yet are created and restricted.
+    if db_path_override().is_none() || (!dir.as_os_str().is_empty() && !dir.exists()) {
+        fs_utils::ensure_dir(dir)?;
+    }
 
     let options = SqliteConnectOptions::new()
         .filename(&path)
         .create_if_missing(true);
-    let pool = SqlitePool::connect_with(options)
+    let pool = connect_with_retry(options, retry::config())
         .await
         .with_context(|| format!("failed to open database at {}", path.display()))?;
 
-    init_schema(&pool).await?;
     fs_utils::set_file_permissions(&path)?;
     Ok(pool)
 }
 
+async fn connect_with_retry(
+    options: SqliteConnectOptions,
+    retry: &RetryConfig,
+) -> Result<SqlitePool, sqlx::Error> {
+    let mut attempt = 1;
+    loop {
+        match SqlitePool::connect_with(options.clone()).await {
+            Ok(pool) => return Ok(pool),
+            Err(_) if retry.can_retry(attempt) => {
+                tokio::time::sleep(retry.delay_for(attempt)).await;
+                attempt += 1;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
 pub fn db_path() -> Result<PathBuf> {
+    if let Some(path) = db_path_override() {
+        return Ok(path);
+    }
+
     let base = paths::data_dir()?;
-    Ok(base.join(DB_FILENAME))
+    Ok(base.join(paths::profile_file_name("ttv", "sqlite")))
+}
+
+// `TTV_DB_PATH` replaces the full path, e.g. to keep separate follow lists.
+fn db_path_override() -> Option<PathBuf> {
+    env::var_os("TTV_DB_PATH")
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
 }
 
 pub async fn upsert_streamer(pool: &SqlitePool, streamer: &TwitchUser) -> Result<()> {
@@ -52,17 +218,24 @@ pub async fn upsert_streamer(pool: &SqlitePool, streamer: &TwitchUser) -> Result
     Ok(())
 }
 
+#[derive(Debug, Serialize)]
 pub struct DbStreamer {
     pub id: String,
     pub name: String,
     pub display_name: String,
+    pub created_at: String,
+    // Not part of the follow list itself, so exports leave it out.
+    #[serde(skip)]
+    pub last_online_at: Option<String>,
 }
 
 pub async fn list_streamers(pool: &SqlitePool) -> Result<Vec<DbStreamer>> {
-    let rows = sqlx::query("SELECT id, name, display_name FROM streamers ORDER BY name")
-        .fetch_all(pool)
-        .await
-        .context("failed to load streamers")?;
+    let rows = sqlx::query(
+        "SELECT id, name, display_name, created_at, last_online_at FROM streamers ORDER BY name",
+    )
+    .fetch_all(pool)
+    .await
+    .context("failed to load streamers")?;
 
     let mut streamers = Vec::with_capacity(rows.len());
     for row in rows {
@@ -70,36 +243,509 @@ pub async fn list_streamers(pool: &SqlitePool) -> Result<Vec<DbStreamer>> {
             id: row.try_get("id")?,
             name: row.try_get("name")?,
             display_name: row.try_get("display_name")?,
+            created_at: row.try_get("created_at")?,
+            last_online_at: row.try_get("last_online_at")?,
         });
     }
 
     Ok(streamers)
 }
 
-pub async fn delete_streamer_by_login(pool: &SqlitePool, login: &str) -> Result<u64> {
-    let result = sqlx::query("DELETE FROM streamers WHERE lower(name) = lower(?1)")
+/// `at` is stored as RFC3339 UTC with a `Z` suffix.
+pub async fn mark_streamers_online(pool: &SqlitePool, ids: &[String], at: &str) -> Result<()> {
+    for id in ids {
+        sqlx::query("UPDATE streamers SET last_online_at = ?1 WHERE id = ?2")
+            .bind(at)
+            .bind(id)
+            .execute(pool)
+            .await
+            .context("failed to update last online time")?;
+    }
+    Ok(())
+}
+
+pub async fn is_followed(pool: &SqlitePool, login: &str) -> Result<bool> {
+    let row = sqlx::query("SELECT 1 FROM streamers WHERE lower(name) = lower(?1)")
         .bind(login)
+        .fetch_optional(pool)
+        .await
+        .context("failed to load streamer")?;
+    Ok(row.is_some())
+}
+
+/// Returns `None` both for unknown logins and for streamers without a stored
+/// quality.
+pub async fn preferred_quality(pool: &SqlitePool, login: &str) -> Result<Option<String>> {
+    let row = sqlx::query("SELECT preferred_quality FROM streamers WHERE lower(name) = lower(?1)")
+        .bind(login)
+        .fetch_optional(pool)
+        .await
+        .context("failed to load preferred quality")?;
+    match row {
+        Some(row) => Ok(row.try_get("preferred_quality")?),
+        None => Ok(None),
+    }
+}
+
+/// `None` clears the stored quality. Returns the number of updated streamers.
+pub async fn set_preferred_quality(
+    pool: &SqlitePool,
+    login: &str,
+    quality: Option<&str>,
+) -> Result<u64> {
+    let result = sqlx::query(
+        "UPDATE streamers SET preferred_quality = ?1, updated_at = CURRENT_TIMESTAMP WHERE lower(name) = lower(?2)",
+    )
+    .bind(quality)
+    .bind(login)
+    .execute(pool)
+    .await
+    .context("failed to update preferred quality")?;
+    Ok(result.rows_affected())
+}
+
+/// Deletes the streamers with any of `logins`, ignoring case, and returns
+/// the stored logins of the deleted rows; their count is the number of
+/// affected rows.
+pub async fn delete_streamers_by_logins(
+    pool: &SqlitePool,
+    logins: &[String],
+) -> Result<Vec<String>> {
+    let mut tx = pool.begin().await.context("failed to start transaction")?;
+    let mut deleted = Vec::new();
+    // Only very long lists need more than one statement.
+    for chunk in logins.chunks(MAX_BIND_PARAMS) {
+        let mut query = QueryBuilder::<Sqlite>::new("DELETE FROM streamers WHERE lower(name) IN (");
+        let mut values = query.separated(", ");
+        for login in chunk {
+            values.push_bind(login.to_lowercase());
+        }
+        values.push_unseparated(") RETURNING name");
+        let rows = query
+            .build()
+            .fetch_all(&mut *tx)
+            .await
+            .context("failed to delete streamers")?;
+        for row in rows {
+            deleted.push(row.try_get("name")?);
+        }
+    }
+    tx.commit().await.context("failed to commit transaction")?;
+    Ok(deleted)
+}
+
+pub async fn delete_streamer_by_id(pool: &SqlitePool, id: &str) -> Result<u64> {
+    let result = sqlx::query("DELETE FROM streamers WHERE id = ?1")
+        .bind(id)
         .execute(pool)
         .await
         .context("failed to delete streamer")?;
     Ok(result.rows_affected())
 }
 
-async fn init_schema(pool: &SqlitePool) -> Result<()> {
+pub struct WatchRecord {
+    pub login: String,
+    pub url: String,
+    pub started_at: String,
+    pub duration_secs: i64,
+}
+
+pub async fn insert_watch_record(pool: &SqlitePool, record: &WatchRecord) -> Result<()> {
     sqlx::query(
+        "INSERT INTO watch_history (login, url, started_at, duration_secs) VALUES (?1, ?2, ?3, ?4)",
+    )
+    .bind(&record.login)
+    .bind(&record.url)
+    .bind(&record.started_at)
+    .bind(record.duration_secs)
+    .execute(pool)
+    .await
+    .context("failed to record watch history")?;
+    Ok(())
+}
+
+/// `started_at` is stored as RFC3339 UTC with a `Z` suffix, so `since` must
+/// use the same format for the string comparison to hold.
+pub async fn list_watch_history(
+    pool: &SqlitePool,
+    login: Option<&str>,
+    since: Option<&str>,
+) -> Result<Vec<WatchRecord>> {
+    let rows = sqlx::query(
         r#"
-        CREATE TABLE IF NOT EXISTS streamers (
-            uid INTEGER PRIMARY KEY AUTOINCREMENT,
-            id TEXT NOT NULL UNIQUE,
-            name TEXT NOT NULL,
-            display_name TEXT NOT NULL,
-            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        SELECT login, url, started_at, duration_secs FROM watch_history
+        WHERE (?1 IS NULL OR lower(login) = lower(?1))
+          AND (?2 IS NULL OR started_at >= ?2)
+        ORDER BY started_at
+        "#,
+    )
+    .bind(login)
+    .bind(since)
+    .fetch_all(pool)
+    .await
+    .context("failed to load watch history")?;
+
+    let mut records = Vec::with_capacity(rows.len());
+    for row in rows {
+        records.push(WatchRecord {
+            login: row.try_get("login")?,
+            url: row.try_get("url")?,
+            started_at: row.try_get("started_at")?,
+            duration_secs: row.try_get("duration_secs")?,
+        });
+    }
+
+    Ok(records)
+}
+
+/// Returns the cached users for `logins` refreshed at or after
+/// `refreshed_since` (RFC3339 UTC with a `Z` suffix, like `refreshed_at`).
+pub async fn cached_users_by_login(
+    pool: &SqlitePool,
+    logins: &[String],
+    refreshed_since: &str,
+) -> Result<Vec<TwitchUser>> {
+    let mut users = Vec::new();
+    for login in logins {
+        let row = sqlx::query(
+            r#"
+            SELECT id, login, display_name FROM user_cache
+            WHERE lower(login) = lower(?1) AND refreshed_at >= ?2
+            "#,
+        )
+        .bind(login)
+        .bind(refreshed_since)
+        .fetch_optional(pool)
+        .await
+        .context("failed to load cached users")?;
+
+        if let Some(row) = row {
+            users.push(TwitchUser {
+                id: row.try_get("id")?,
+                login: row.try_get("login")?,
+                display_name: row.try_get("display_name")?,
+            });
+        }
+    }
+    Ok(users)
+}
+
+pub async fn cache_users(
+    pool: &SqlitePool,
+    users: &[TwitchUser],
+    refreshed_at: &str,
+) -> Result<()> {
+    for user in users {
+        sqlx::query(
+            r#"
+            INSERT INTO user_cache (id, login, display_name, refreshed_at)
+            VALUES (?1, ?2, ?3, ?4)
+            ON CONFLICT(id) DO UPDATE SET
+                login = excluded.login,
+                display_name = excluded.display_name,
+                refreshed_at = excluded.refreshed_at
+            "#,
         )
+        .bind(&user.id)
+        .bind(&user.login)
+        .bind(&user.display_name)
+        .bind(refreshed_at)
+        .execute(pool)
+        .await
+        .context("failed to cache users")?;
+    }
+    Ok(())
+}
+
+/// Returns the game cached for the lowercased `query` if it was refreshed at
+/// or after `refreshed_since`.
+pub async fn cached_game(
+    pool: &SqlitePool,
+    query: &str,
+    refreshed_since: &str,
+) -> Result<Option<TwitchGame>> {
+    let row =
+        sqlx::query("SELECT id, name FROM game_cache WHERE query = ?1 AND refreshed_at >= ?2")
+            .bind(query.to_lowercase())
+            .bind(refreshed_since)
+            .fetch_optional(pool)
+            .await
+            .context("failed to load cached game")?;
+
+    match row {
+        Some(row) => Ok(Some(TwitchGame {
+            id: row.try_get("id")?,
+            name: row.try_get("name")?,
+        })),
+        None => Ok(None),
+    }
+}
+
+pub async fn cache_game(
+    pool: &SqlitePool,
+    query: &str,
+    game: &TwitchGame,
+    refreshed_at: &str,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO game_cache (query, id, name, refreshed_at)
+        VALUES (?1, ?2, ?3, ?4)
+        ON CONFLICT(query) DO UPDATE SET
+            id = excluded.id,
+            name = excluded.name,
+            refreshed_at = excluded.refreshed_at
         "#,
     )
+    .bind(query.to_lowercase())
+    .bind(&game.id)
+    .bind(&game.name)
+    .bind(refreshed_at)
     .execute(pool)
     .await
-    .context("failed to initialize database schema")?;
+    .context("failed to cache game")?;
     Ok(())
 }
+
+async fn integrity_check(pool: &SqlitePool) -> Result<Vec<String>> {
+    let rows = sqlx::query("PRAGMA integrity_check")
+        .fetch_all(pool)
+        .await
+        .context("failed to run integrity check")?;
+
+    let mut problems = Vec::new();
+    for row in rows {
+        let message: String = row.try_get(0)?;
+        if message != "ok" {
+            problems.push(message);
+        }
+    }
+    Ok(problems)
+}
+
+async fn foreign_key_check(pool: &SqlitePool) -> Result<Vec<String>> {
+    let rows = sqlx::query("PRAGMA foreign_key_check")
+        .fetch_all(pool)
+        .await
+        .context("failed to run foreign key check")?;
+
+    let mut problems = Vec::with_capacity(rows.len());
+    for row in rows {
+        let table: String = row.try_get(0)?;
+        let rowid: Option<i64> = row.try_get(1)?;
+        let parent: String = row.try_get(2)?;
+        problems.push(format!(
+            "foreign key violation in {} (rowid {}) referencing {}",
+            table,
+            rowid
+                .map(|id| id.to_string())
+                .unwrap_or_else(|| "?".to_string()),
+            parent
+        ));
+    }
+    Ok(problems)
+}
+
+struct Migration {
+    version: i64,
+    description: &'static str,
+    statements: &'static [&'static str],
+}
+
+// Applied in order, each in its own transaction. Never edit a released step;
+// add a new one instead.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "create streamers, watch_history and user_cache",
+        statements: &[
+            r#"
+            CREATE TABLE IF NOT EXISTS streamers (
+                uid INTEGER PRIMARY KEY AUTOINCREMENT,
+                id TEXT NOT NULL UNIQUE,
+                name TEXT NOT NULL,
+                display_name TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS watch_history (
+                uid INTEGER PRIMARY KEY AUTOINCREMENT,
+                login TEXT NOT NULL,
+                url TEXT NOT NULL,
+                started_at TEXT NOT NULL,
+                duration_secs INTEGER NOT NULL
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS user_cache (
+                id TEXT PRIMARY KEY,
+                login TEXT NOT NULL,
+                display_name TEXT NOT NULL,
+                refreshed_at TEXT NOT NULL
+            )
+            "#,
+        ],
+    },
+    Migration {
+        version: 2,
+        description: "add streamers.last_online_at",
+        statements: &["ALTER TABLE streamers ADD COLUMN last_online_at TEXT"],
+    },
+    Migration {
+        version: 3,
+        description: "add streamers.preferred_quality",
+        statements: &["ALTER TABLE streamers ADD COLUMN preferred_quality TEXT"],
+    },
+    Migration {
+        version: 4,
+        description: "create game_cache",
+        statements: &[r#"
+            CREATE TABLE IF NOT EXISTS game_cache (
+                query TEXT PRIMARY KEY,
+                id TEXT NOT NULL,
+                name TEXT NOT NULL,
+                refreshed_at TEXT NOT NULL
+            )
+            "#],
+    },
+];
+
+async fn init_schema(pool: &SqlitePool) -> Result<()> {
+    let current = schema_version(pool).await?;
+    let latest = MIGRATIONS.last().map_or(0, |migration| migration.version);
+    if current > latest {
+        bail!(
+            "The database schema (version {}) is newer than this version of ttv supports (version {}). Update ttv.",
+            current,
+            latest
+        );
+    }
+
+    if !has_table(pool, "schema_version").await? {
+        create_schema_version_table(pool, current).await?;
+    }
+    for migration in pending_migrations(current) {
+        apply_migration(pool, migration).await?;
+    }
+    Ok(())
+}
+
+fn pending_migrations(current: i64) -> impl Iterator<Item = &'static Migration> {
+    MIGRATIONS
+        .iter()
+        .filter(move |migration| migration.version > current)
+}
+
+// Databases from before `schema_version` existed are versioned by the
+// columns they already have.
+async fn schema_version(pool: &SqlitePool) -> Result<i64> {
+    if !has_table(pool, "schema_version").await? {
+        if !has_table(pool, "streamers").await? {
+            return Ok(0);
+        }
+        return if has_column(pool, "streamers", "preferred_quality").await? {
+            Ok(3)
+        } else if has_column(pool, "streamers", "last_online_at").await? {
+            Ok(2)
+        } else {
+            Ok(1)
+        };
+    }
+
+    let row = sqlx::query("SELECT COALESCE(MAX(version), 0) AS version FROM schema_version")
+        .fetch_one(pool)
+        .await
+        .context("failed to read database schema version")?;
+    Ok(row.try_get("version")?)
+}
+
+// Steps up to `baseline` are already reflected in the schema and are only
+// recorded. Version 1 only creates missing tables, so it is re-run for older
+// databases that predate some of them.
+async fn create_schema_version_table(pool: &SqlitePool, baseline: i64) -> Result<()> {
+    let mut tx = pool
+        .begin()
+        .await
+        .context("failed to create schema_version table")?;
+    sqlx::query(
+        r#"
+        CREATE TABLE schema_version (
+            version INTEGER PRIMARY KEY,
+            description TEXT NOT NULL,
+            applied_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+    )
+    .execute(&mut *tx)
+    .await
+    .context("failed to create schema_version table")?;
+    if baseline >= 1 {
+        for statement in MIGRATIONS[0].statements {
+            sqlx::query(statement)
+                .execute(&mut *tx)
+                .await
+                .context("failed to migrate database schema")?;
+        }
+    }
+    for migration in MIGRATIONS
+        .iter()
+        .take_while(|migration| migration.version <= baseline)
+    {
+        record_migration(&mut tx, migration).await?;
+    }
+    tx.commit()
+        .await
+        .context("failed to create schema_version table")?;
+    Ok(())
+}
+
+async fn apply_migration(pool: &SqlitePool, migration: &Migration) -> Result<()> {
+    let mut tx = pool.begin().await.context("failed to start migration")?;
+    for statement in migration.statements {
+        sqlx::query(statement)
+            .execute(&mut *tx)
+            .await
+            .with_context(|| format!("failed to apply migration {}", migration.version))?;
+    }
+    record_migration(&mut tx, migration).await?;
+    tx.commit()
+        .await
+        .with_context(|| format!("failed to apply migration {}", migration.version))?;
+    Ok(())
+}
+
+async fn record_migration(conn: &mut SqliteConnection, migration: &Migration) -> Result<()> {
+    sqlx::query("INSERT INTO schema_version (version, description) VALUES (?1, ?2)")
+        .bind(migration.version)
+        .bind(migration.description)
+        .execute(conn)
+        .await
+        .context("failed to record migration")?;
+    Ok(())
+}
+
+async fn has_table(pool: &SqlitePool, table: &str) -> Result<bool> {
+    let row = sqlx::query("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1")
+        .bind(table)
+        .fetch_optional(pool)
+        .await
+        .context("failed to inspect database schema")?;
+    Ok(row.is_some())
+}
+
+async fn has_column(pool: &SqlitePool, table: &str, column: &str) -> Result<bool> {
+    let rows = sqlx::query(&format!("PRAGMA table_info({})", table))
+        .fetch_all(pool)
+        .await
+        .context("failed to inspect database schema")?;
+
+    for row in rows {
+        let name: String = row.try_get("name")?;
+        if name == column {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
@@ -0,0 +1,213 @@
+use std::fmt;
+use std::io;
+
+use reqwest::StatusCode;
+use serde::Serialize;
+
+/// A Twitch API or auth request that Twitch answered with an error status.
+#[derive(Debug)]
+pub struct TwitchError {
+    pub status: StatusCode,
+    message: String,
+}
+
+impl TwitchError {
+    pub fn error(status: StatusCode, message: impl Into<String>) -> anyhow::Error {
+        anyhow::Error::new(Self {
+            status,
+            message: message.into(),
+        })
+    }
+}
+
+impl fmt::Display for TwitchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for TwitchError {}
+
+/// Configuration that is missing or unusable, such as credentials that were
+/// never set or a config file that does not parse.
+#[derive(Debug)]
+pub struct ConfigError {
+    message: String,
+}
+
+impl ConfigError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> anyhow::Error {
+        anyhow::Error::new(Self::new(message))
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Something the user asked for that does not exist, e.g. an unknown game.
+#[derive(Debug)]
+pub struct NotFoundError {
+    message: String,
+}
+
+impl NotFoundError {
+    pub fn error(message: impl Into<String>) -> anyhow::Error {
+        anyhow::Error::new(Self {
+            message: message.into(),
+        })
+    }
+}
+
+impl fmt::Display for NotFoundError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for NotFoundError {}
+
+/// A login that could not be resolved to a Twitch user.
+#[derive(Debug)]
+pub enum UserLookupError {
+    /// Not a well-formed login, so Twitch was not asked.
+    InvalidLogin(String),
+    /// Twitch returned no user for a login that is not followed.
+    NotFound(String),
+    /// Twitch returned no user for a followed login, so the account was
+    /// suspended, deleted, or renamed since it was followed.
+    Unavailable(String),
+}
+
+impl fmt::Display for UserLookupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidLogin(login) => write!(
+                f,
+                "`{login}` is not a valid Twitch login; logins only contain letters, digits, and underscores."
+            ),
+            Self::NotFound(login) => write!(
+                f,
+                "No streamer found with login `{login}`. Check the spelling, or use `ttv search` to find the channel."
+            ),
+            Self::Unavailable(login) => write!(
+                f,
+                "Twitch returned no data for the followed streamer `{login}`. The account may be suspended or deleted; if it was renamed, run `ttv refresh`."
+            ),
+        }
+    }
+}
+
+impl std::error::Error for UserLookupError {}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    Config,
+    Unauthorized,
+    Forbidden,
+    RateLimited,
+    InvalidInput,
+    NotFound,
+    Api,
+    Network,
+    Database,
+    Parse,
+    Io,
+    Other,
+}
+
+impl ErrorKind {
+    // The first error in the chain with a known type decides the kind.
+    pub fn of(err: &anyhow::Error) -> Self {
+        // Config errors are usually attached as context, which `chain` does
+        // not expose as its own type.
+        if err.downcast_ref::<ConfigError>().is_some() {
+            return Self::Config;
+        }
+        for cause in err.chain() {
+            if let Some(err) = cause.downcast_ref::<TwitchError>() {
+                return match err.status {
+                    StatusCode::UNAUTHORIZED => Self::Unauthorized,
+                    StatusCode::FORBIDDEN => Self::Forbidden,
+                    StatusCode::TOO_MANY_REQUESTS => Self::RateLimited,
+                    _ => Self::Api,
+                };
+            }
+            if let Some(err) = cause.downcast_ref::<UserLookupError>() {
+                return match err {
+                    UserLookupError::InvalidLogin(_) => Self::InvalidInput,
+                    UserLookupError::NotFound(_) | UserLookupError::Unavailable(_) => {
+                        Self::NotFound
+                    }
+                };
+            }
+            if cause.is::<NotFoundError>() {
+                return Self::NotFound;
+            }
+            if cause.is::<reqwest::Error>() {
+                return Self::Network;
+            }
+            if cause.is::<sqlx::Error>() {
+                return Self::Database;
+            }
+            if cause.is::<serde_json::Error>() {
+                return Self::Parse;
+            }
+            if cause.is::<io::Error>() {
+                return Self::Io;
+            }
+        }
+        Self::Other
+    }
+
+    /// Process exit code: 2 for config problems, 3 for Twitch and network
+    /// failures, 4 when something was not found, and 1 for everything else.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Self::Config => 2,
+            Self::Unauthorized
+            | Self::Forbidden
+            | Self::RateLimited
+            | Self::Api
+            | Self::Network => 3,
+            Self::NotFound => 4,
+            Self::InvalidInput | Self::Database | Self::Parse | Self::Io | Self::Other => 1,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonError {
+    error: JsonErrorBody,
+}
+
+#[derive(Serialize)]
+struct JsonErrorBody {
+    kind: ErrorKind,
+    message: String,
+}
+
+/// Prints `err` to stderr as `{"error":{"kind":...,"message":...}}`.
+pub fn print_json(err: &anyhow::Error) {
+    let output = JsonError {
+        error: JsonErrorBody {
+            kind: ErrorKind::of(err),
+            message: format!("{err:#}"),
+        },
+    };
+    match serde_json::to_string(&output) {
+        Ok(line) => eprintln!("{line}"),
+        Err(_) => eprintln!("{err:#}"),
+    }
+}
@@ -0,0 +1,74 @@
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::{Args, ValueEnum};
+
+use crate::db::{self, DbStreamer};
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Args)]
+#[command(about = "Export followed streamers to JSON or CSV")]
+pub struct ExportArgs {
+    #[arg(long, value_enum, default_value_t = ExportFormat::Json, help = "Output format")]
+    pub format: ExportFormat,
+    #[arg(long, value_name = "PATH", help = "Write to a file instead of stdout")]
+    pub output: Option<PathBuf>,
+}
+
+pub async fn run(args: ExportArgs) -> Result<()> {
+    let pool = db::connect().await?;
+    let streamers = db::list_streamers(&pool).await?;
+
+    let contents = match args.format {
+        ExportFormat::Json => {
+            let mut json = serde_json::to_string_pretty(&streamers)
+                .context("failed to serialize streamers")?;
+            json.push('\n');
+            json
+        }
+        ExportFormat::Csv => to_csv(&streamers),
+    };
+
+    match args.output {
+        Some(path) => {
+            fs::write(&path, contents)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            eprintln!(
+                "Exported {} streamer(s) to {}.",
+                streamers.len(),
+                path.display()
+            );
+        }
+        None => print!("{}", contents),
+    }
+    Ok(())
+}
+
+fn to_csv(streamers: &[DbStreamer]) -> String {
+    let mut out = String::from("id,name,display_name,created_at\n");
+    for streamer in streamers {
+        let fields = [
+            csv_field(&streamer.id),
+            csv_field(&streamer.name),
+            csv_field(&streamer.display_name),
+            csv_field(&streamer.created_at),
+        ];
+        out.push_str(&fields.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -1,72 +1,320 @@
 use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
 
-use anyhow::{Result, bail};
+use anyhow::{Context, Result, bail};
 use clap::Args;
+use log::info;
 
-use crate::{auth, config, db, twitch};
+use crate::error::{ConfigError, NotFoundError};
+use crate::twitch::{TwitchClient, TwitchUser};
+use crate::{auth, cache, config, db, login, prompt};
 
 #[derive(Debug, Args)]
 #[command(about = "Follow Twitch streamers locally")]
 pub struct FollowArgs {
-    #[arg(value_name = "LOGIN", required = true, num_args = 1.., help = "Twitch login name(s) to follow")]
+    #[arg(
+        value_name = "LOGIN",
+        required_unless_present_any = ["ids", "from_file", "from_clipboard", "sync"],
+        num_args = 1..,
+        help = "Twitch login name(s) to follow"
+    )]
     pub logins: Vec<String>,
-    #[arg(long, help = "Print verbose request and update details")]
-    pub verbose: bool,
+    #[arg(
+        long = "id",
+        value_name = "ID",
+        num_args = 1..,
+        conflicts_with = "logins",
+        help = "Twitch user ID(s) to follow instead of logins"
+    )]
+    pub ids: Vec<String>,
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with = "ids",
+        help = "Read logins from a file (one per line; blank lines and # comments are ignored)"
+    )]
+    pub from_file: Option<PathBuf>,
+    #[arg(
+        long,
+        conflicts_with = "ids",
+        help = "Read logins from the system clipboard (separated by whitespace)"
+    )]
+    pub from_clipboard: bool,
+    #[arg(
+        long,
+        conflicts_with_all = ["logins", "ids", "from_file", "from_clipboard"],
+        help = "Follow every channel your Twitch account follows (needs `ttv auth --user`)"
+    )]
+    pub sync: bool,
+    #[arg(
+        long,
+        requires = "sync",
+        conflicts_with_all = ["logins", "ids", "from_file", "from_clipboard"],
+        help = "With --sync, also unfollow local streamers not followed on Twitch"
+    )]
+    pub prune: bool,
+    #[arg(
+        long,
+        help = "Look up the streamers and print what would be followed without saving"
+    )]
+    pub dry_run: bool,
 }
 
 pub async fn run(args: FollowArgs) -> Result<()> {
+    if args.sync {
+        return sync(&args).await;
+    }
+
+    let by_id = !args.ids.is_empty();
+    if let Some(invalid) = args.ids.iter().find(|id| !is_valid_id(id)) {
+        bail!("Invalid Twitch user ID: {invalid}");
+    }
+
+    let mut logins = args.logins.clone();
+    if let Some(path) = &args.from_file {
+        logins.extend(read_logins_file(path)?);
+    }
+    if args.from_clipboard {
+        logins.extend(read_logins_clipboard()?);
+    }
+    // Twitch rejects a whole request for one malformed login, so those are
+    // reported and skipped before anything is fetched.
+    let (logins, invalid): (Vec<String>, Vec<String>) = login::dedup_logins(logins)
+        .into_iter()
+        .partition(|login| login::is_valid_login(login));
+    if !invalid.is_empty() {
+        eprintln!("Invalid Twitch login(s), skipped: {}", invalid.join(", "));
+    }
+    if !by_id && logins.is_empty() {
+        bail!("No valid login names provided.");
+    }
+
     let mut config = config::load_config()?;
     if config::token_needs_refresh(&config) {
-        if args.verbose {
-            eprintln!("[INFO] Access token missing or expired, running auth");
-        }
-        auth::run(auth::AuthArgs {
-            show: false,
-            verbose: args.verbose,
-        })
-        .await?;
+        info!("Access token missing or expired, running auth");
+        auth::run(auth::AuthArgs::default()).await?;
         config = config::load_config()?;
     }
 
-    let client_id = config::require_client_id(&config)?;
-    let access_token = config::require_access_token(&config)?;
+    let client = TwitchClient::from_config(&config)?;
 
-    if args.verbose {
-        eprintln!("[INFO] Fetching {} streamer(s) from Twitch", args.logins.len());
-    }
-    let users = twitch::fetch_users_by_login(client_id, access_token, &args.logins).await?;
+    let requested = if by_id { &args.ids } else { &logins };
+    info!("Fetching {} streamer(s) from Twitch", requested.len());
+    let pool = db::connect().await?;
+    // A dry run asks Twitch directly so that nothing, not even the user
+    // cache, is written.
+    let users = if by_id {
+        client.fetch_users_by_id(&args.ids).await?
+    } else if args.dry_run {
+        client.fetch_users_by_login(&logins).await?
+    } else {
+        cache::users_by_login(&pool, &config, &client, &logins).await?
+    };
     if users.is_empty() {
-        bail!("No streamers found for the provided login names.");
+        if by_id {
+            return Err(NotFoundError::error(
+                "No streamers found for the provided user IDs.",
+            ));
+        }
+        return Err(NotFoundError::error(format!(
+            "No streamers found for the provided login names (misspelled, suspended, or deleted): {}",
+            logins.join(", ")
+        )));
     }
 
-    let pool = db::connect().await?;
-    if args.verbose {
-        if let Ok(path) = db::db_path() {
-            eprintln!("[INFO] Using database at {}", path.display());
-        }
+    if let Ok(path) = db::db_path() {
+        info!("Using database at {}", path.display());
     }
     for user in &users {
-        db::upsert_streamer(&pool, user).await?;
-        if args.verbose {
-            eprintln!(
-                "[INFO] Followed {} ({})",
-                user.login, user.display_name
-            );
+        if args.dry_run {
+            println!("Would follow {} ({})", user.login, user.display_name);
+            continue;
         }
+        db::upsert_streamer(&pool, user).await?;
+        info!("Followed {} ({})", user.login, user.display_name);
     }
 
-    let found: HashSet<String> = users.iter().map(|user| user.login.to_lowercase()).collect();
-    let missing: Vec<String> = args
-        .logins
+    let found: HashSet<String> = users
         .iter()
-        .filter(|login| !found.contains(&login.to_lowercase()))
+        .map(|user| {
+            if by_id {
+                user.id.clone()
+            } else {
+                user.login.to_lowercase()
+            }
+        })
+        .collect();
+    let missing: Vec<String> = requested
+        .iter()
+        .filter(|value| !found.contains(&value.to_lowercase()))
         .cloned()
         .collect();
 
     if !missing.is_empty() {
-        eprintln!("Not found on Twitch: {}", missing.join(", "));
+        eprintln!(
+            "Not found on Twitch (misspelled, suspended, or deleted): {}",
+            missing.join(", ")
+        );
+    }
+
+    if args.dry_run {
+        println!("Would follow {} streamer(s).", users.len());
+    } else {
+        println!("Followed {} streamer(s).", users.len());
+    }
+    Ok(())
+}
+
+// Mirrors the follows of the user behind the `auth --user` token. Local
+// follows that only exist locally are kept unless `--prune` is given.
+async fn sync(args: &FollowArgs) -> Result<()> {
+    let mut config = config::load_config()?;
+    if config::user_token_needs_refresh(&config) {
+        if config.twitch.user_refresh_token.is_none() {
+            return Err(ConfigError::error(
+                "Missing or expired Twitch user token. Run `ttv auth --user`.",
+            ));
+        }
+        info!("User token expired, refreshing");
+        auth::refresh_user_token(&mut config).await?;
     }
+    if !config
+        .twitch
+        .user_scopes
+        .iter()
+        .any(|scope| scope == auth::FOLLOWS_SCOPE)
+    {
+        return Err(ConfigError::error(format!(
+            "The stored user token lacks the `{}` scope. Run `ttv auth --user` again.",
+            auth::FOLLOWS_SCOPE
+        )));
+    }
+    let user_id = config
+        .twitch
+        .user_id
+        .clone()
+        .filter(|id| !id.trim().is_empty())
+        .ok_or_else(|| {
+            ConfigError::error("Missing Twitch user ID. Run `ttv auth --user` again.")
+        })?;
+
+    let client = TwitchClient::from_user_config(&config)?;
+    let channels = client.fetch_followed_channels(&user_id).await?;
+    info!("Twitch reports {} followed channel(s)", channels.len());
+
+    let pool = db::connect().await?;
+    let local = db::list_streamers(&pool).await?;
+    let local_ids: HashSet<&str> = local.iter().map(|streamer| streamer.id.as_str()).collect();
+    let remote_ids: HashSet<&str> = channels
+        .iter()
+        .map(|channel| channel.broadcaster_id.as_str())
+        .collect();
 
-    println!("Followed {} streamer(s).", users.len());
+    let mut added = 0;
+    for channel in &channels {
+        let is_new = !local_ids.contains(channel.broadcaster_id.as_str());
+        if args.dry_run {
+            if is_new {
+                println!(
+                    "Would follow {} ({})",
+                    channel.broadcaster_login, channel.broadcaster_name
+                );
+                added += 1;
+            }
+            continue;
+        }
+        // Existing follows are upserted too, which picks up renames.
+        let user = TwitchUser {
+            id: channel.broadcaster_id.clone(),
+            login: channel.broadcaster_login.clone(),
+            display_name: channel.broadcaster_name.clone(),
+        };
+        db::upsert_streamer(&pool, &user).await?;
+        if is_new {
+            println!("Followed {} ({})", user.login, user.display_name);
+            added += 1;
+        }
+    }
+
+    let mut removed = 0;
+    let stale: Vec<&db::DbStreamer> = local
+        .iter()
+        .filter(|streamer| !remote_ids.contains(streamer.id.as_str()))
+        .collect();
+    if args.prune && args.dry_run {
+        for streamer in &stale {
+            println!("Would unfollow {}", streamer.name);
+        }
+        removed = stale.len();
+    } else if args.prune && !stale.is_empty() {
+        let names: Vec<&str> = stale
+            .iter()
+            .map(|streamer| streamer.name.as_str())
+            .collect();
+        println!(
+            "{} local streamer(s) are not followed on Twitch: {}",
+            stale.len(),
+            names.join(", ")
+        );
+        if prompt::confirm("Unfollow them?")? {
+            for streamer in &stale {
+                if db::delete_streamer_by_id(&pool, &streamer.id).await? > 0 {
+                    println!("Unfollowed {}", streamer.name);
+                    removed += 1;
+                }
+            }
+        }
+    }
+
+    let verb = if args.dry_run { "Would sync" } else { "Synced" };
+    if args.prune {
+        println!(
+            "{verb} {} followed channel(s): {added} new, {removed} removed.",
+            channels.len()
+        );
+    } else {
+        println!(
+            "{verb} {} followed channel(s): {added} new.",
+            channels.len()
+        );
+    }
     Ok(())
 }
+
+fn read_logins_file(path: &Path) -> Result<Vec<String>> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    Ok(contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+fn read_logins_clipboard() -> Result<Vec<String>> {
+    let contents = read_clipboard()?;
+    let logins: Vec<String> = contents.split_whitespace().map(str::to_string).collect();
+    if logins.is_empty() {
+        bail!("The clipboard does not contain any logins.");
+    }
+    Ok(logins)
+}
+
+#[cfg(feature = "clipboard")]
+fn read_clipboard() -> Result<String> {
+    let mut clipboard = arboard::Clipboard::new().context("Could not access the clipboard")?;
+    clipboard
+        .get_text()
+        .context("Could not read text from the clipboard")
+}
+
+#[cfg(not(feature = "clipboard"))]
+fn read_clipboard() -> Result<String> {
+    bail!("This build of ttv has no clipboard support. Rebuild it with `--features clipboard`.")
+}
+
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|ch| ch.is_ascii_digit())
+}
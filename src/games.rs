@@ -0,0 +1,88 @@
+use std::io;
+
+use anyhow::Result;
+use clap::Args;
+use colored::{ColoredString, Colorize};
+use serde::Serialize;
+
+use crate::output::{self, Align, OutputFormat};
+use crate::twitch::{TwitchClient, TwitchGame};
+use crate::{auth, config};
+
+#[derive(Debug, Args)]
+#[command(about = "List the most-viewed game categories on Twitch")]
+pub struct GamesArgs {
+    #[arg(
+        long,
+        default_value_t = 20,
+        value_parser = clap::value_parser!(u8).range(1..=100),
+        help = "Maximum number of categories (1-100)"
+    )]
+    pub limit: u8,
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table, help = "Output format")]
+    pub format: OutputFormat,
+}
+
+#[derive(Serialize)]
+struct JsonGame<'a> {
+    id: &'a str,
+    name: &'a str,
+}
+
+pub async fn run(args: GamesArgs) -> Result<()> {
+    let mut config = config::load_config()?;
+    if config::token_needs_refresh(&config) {
+        auth::run(auth::AuthArgs::default()).await?;
+        config = config::load_config()?;
+    }
+
+    let client = TwitchClient::from_config(&config)?;
+    let games = client.fetch_top_games(args.limit).await?;
+    if games.is_empty() {
+        output::write_empty(&mut io::stdout(), args.format, "No categories found.")?;
+        return Ok(());
+    }
+
+    match args.format {
+        OutputFormat::Table => print_table(&games)?,
+        OutputFormat::Json => {
+            let rows: Vec<JsonGame> = games
+                .iter()
+                .map(|game| JsonGame {
+                    id: &game.id,
+                    name: &game.name,
+                })
+                .collect();
+            output::write_json(&mut io::stdout(), &rows)?;
+        }
+        OutputFormat::Plain => {
+            let rows: Vec<Vec<String>> = games
+                .iter()
+                .map(|game| vec![game.id.clone(), game.name.clone()])
+                .collect();
+            output::write_plain(&mut io::stdout(), &rows)?;
+        }
+    }
+    Ok(())
+}
+
+// Twitch already returns the categories most viewed first.
+fn print_table(games: &[TwitchGame]) -> io::Result<()> {
+    let columns = [
+        ("#", Align::Right),
+        ("name", Align::Left),
+        ("id", Align::Left),
+    ];
+    let rows: Vec<Vec<ColoredString>> = games
+        .iter()
+        .enumerate()
+        .map(|(idx, game)| {
+            vec![
+                (idx + 1).to_string().bold(),
+                game.name.as_str().normal(),
+                game.id.as_str().dimmed(),
+            ]
+        })
+        .collect();
+    output::write_table(&mut io::stdout(), &columns, &rows)
+}
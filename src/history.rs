@@ -0,0 +1,113 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+use clap::{Args, ValueEnum};
+use colored::Colorize;
+
+use crate::db::{self, WatchRecord};
+use crate::export::csv_field;
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum HistoryFormat {
+    Table,
+    Csv,
+}
+
+#[derive(Debug, Args)]
+#[command(about = "Show streams watched with `ttv watch`")]
+pub struct HistoryArgs {
+    #[arg(long, value_enum, default_value_t = HistoryFormat::Table, help = "Output format")]
+    pub format: HistoryFormat,
+    #[arg(long, value_name = "LOGIN", help = "Only show sessions for this login")]
+    pub login: Option<String>,
+    #[arg(
+        long,
+        value_name = "DATE",
+        help = "Only show sessions started on or after this date (YYYY-MM-DD or RFC3339)"
+    )]
+    pub since: Option<String>,
+}
+
+pub async fn run(args: HistoryArgs) -> Result<()> {
+    let since = args.since.as_deref().map(parse_since).transpose()?;
+
+    let pool = db::connect().await?;
+    let records = db::list_watch_history(&pool, args.login.as_deref(), since.as_deref()).await?;
+
+    match args.format {
+        HistoryFormat::Csv => print_csv(&records),
+        HistoryFormat::Table => {
+            if records.is_empty() {
+                println!("No watch history.");
+                return Ok(());
+            }
+            print_table(&records);
+        }
+    }
+    Ok(())
+}
+
+fn parse_since(value: &str) -> Result<String> {
+    let parsed = match NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        Ok(date) => date
+            .and_hms_opt(0, 0, 0)
+            .map(|datetime| datetime.and_utc())
+            .context("invalid --since date")?,
+        Err(_) => DateTime::parse_from_rfc3339(value)
+            .with_context(|| "since must be a YYYY-MM-DD date or an RFC3339 timestamp")?
+            .with_timezone(&Utc),
+    };
+    Ok(parsed.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+fn print_csv(records: &[WatchRecord]) {
+    println!("login,url,started_at,duration_secs");
+    for record in records {
+        println!(
+            "{},{},{},{}",
+            csv_field(&record.login),
+            csv_field(&record.url),
+            csv_field(&record.started_at),
+            record.duration_secs
+        );
+    }
+}
+
+fn print_table(records: &[WatchRecord]) {
+    let login_width = records
+        .iter()
+        .map(|record| record.login.len())
+        .max()
+        .unwrap_or(5)
+        .max("login".len());
+    let started_width = records
+        .iter()
+        .map(|record| record.started_at.len())
+        .max()
+        .unwrap_or(10)
+        .max("started_at".len());
+
+    println!(
+        "{}  {}  {}",
+        format!("{:<login_width$}", "login").cyan().bold(),
+        format!("{:<started_width$}", "started_at").cyan().bold(),
+        "duration".cyan().bold()
+    );
+    for record in records {
+        println!(
+            "{}  {:<started_width$}  {}",
+            format!("{:<login_width$}", record.login).bold(),
+            record.started_at,
+            format_duration(record.duration_secs)
+        );
+    }
+}
+
+fn format_duration(secs: i64) -> String {
+    let secs = secs.max(0);
+    format!(
+        "{:02}:{:02}:{:02}",
+        secs / 3600,
+        (secs % 3600) / 60,
+        secs % 60
+    )
+}
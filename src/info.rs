@@ -0,0 +1,114 @@
+use anyhow::{Context, Result};
+use clap::Args;
+use serde::Serialize;
+
+use crate::twitch::TwitchClient;
+use crate::{auth, cache, config, db};
+
+const RECENT_VODS: usize = 3;
+
+#[derive(Debug, Args)]
+#[command(about = "Show a summary of a Twitch streamer")]
+pub struct InfoArgs {
+    #[arg(value_name = "LOGIN", help = "Twitch login name")]
+    pub login: String,
+    #[arg(long, help = "Print the summary as JSON")]
+    pub json: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct Info {
+    login: String,
+    display_name: String,
+    user_id: String,
+    live: bool,
+    stream: Option<StreamInfo>,
+    recent_vods: Vec<VodInfo>,
+}
+
+#[derive(Debug, Serialize)]
+struct StreamInfo {
+    game: String,
+    title: String,
+    viewers: u64,
+    started_at: String,
+}
+
+#[derive(Debug, Serialize)]
+struct VodInfo {
+    id: String,
+    title: String,
+    duration: String,
+    created_at: String,
+}
+
+pub async fn run(args: InfoArgs) -> Result<()> {
+    let mut config = config::load_config()?;
+    if config::token_needs_refresh(&config) {
+        auth::run(auth::AuthArgs::default()).await?;
+        config = config::load_config()?;
+    }
+
+    let client = TwitchClient::from_config(&config)?;
+
+    let pool = db::connect().await?;
+    let user = cache::user_by_login(&pool, &config, &client, &args.login).await?;
+    let stream = client
+        .fetch_streams_by_user_ids(std::slice::from_ref(&user.id))
+        .await?
+        .into_iter()
+        .next();
+    let vods = client
+        .fetch_vods_by_user_id(&user.id, Some("archive"), 20)
+        .await?;
+
+    let info = Info {
+        login: user.login,
+        display_name: user.display_name,
+        user_id: user.id,
+        live: stream.is_some(),
+        stream: stream.map(|stream| StreamInfo {
+            game: stream.game_name,
+            title: stream.title,
+            viewers: stream.viewer_count,
+            started_at: stream.started_at.to_rfc3339(),
+        }),
+        recent_vods: vods
+            .into_iter()
+            .take(RECENT_VODS)
+            .map(|vod| VodInfo {
+                id: vod.id,
+                title: vod.title,
+                duration: vod.duration,
+                created_at: vod.created_at,
+            })
+            .collect(),
+    };
+
+    if args.json {
+        let json = serde_json::to_string_pretty(&info).context("failed to serialize info")?;
+        println!("{json}");
+        return Ok(());
+    }
+
+    println!("{} ({})", info.display_name, info.login);
+    println!("  user id: {}", info.user_id);
+    match &info.stream {
+        Some(stream) => {
+            println!("  live:    yes, since {}", stream.started_at);
+            println!("  game:    {}", stream.game);
+            println!("  title:   {}", stream.title);
+            println!("  viewers: {}", stream.viewers);
+        }
+        None => println!("  live:    no"),
+    }
+    if info.recent_vods.is_empty() {
+        println!("  No VODs.");
+    } else {
+        println!("  Recent VODs:");
+        for vod in &info.recent_vods {
+            println!("    [{}] {} ({})", vod.created_at, vod.title, vod.duration);
+        }
+    }
+    Ok(())
+}
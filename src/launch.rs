@@ -0,0 +1,88 @@
+use anyhow::{Result, bail};
+use clap::Args;
+
+use crate::config::{self, LaunchOverride};
+use crate::{login, streamlink};
+
+#[derive(Debug, Args)]
+#[command(about = "Set per-streamer launch overrides used by watch")]
+pub struct SetLaunchArgs {
+    #[arg(value_name = "LOGIN", help = "Twitch login name")]
+    pub login: String,
+    #[arg(
+        long,
+        value_parser = streamlink::parse_quality,
+        help = "Stream quality or fallback list like 1080p60,720p,best for this streamer"
+    )]
+    pub quality: Option<String>,
+    #[arg(long, help = "Player executable for this streamer")]
+    pub player: Option<String>,
+    #[arg(
+        long = "extra-arg",
+        value_name = "ARG",
+        allow_hyphen_values = true,
+        help = "Extra streamlink argument for this streamer (repeatable; replaces previous ones)"
+    )]
+    pub extra_args: Vec<String>,
+    #[arg(
+        long,
+        conflicts_with_all = ["quality", "player", "extra_args"],
+        help = "Remove all overrides for this streamer"
+    )]
+    pub clear: bool,
+}
+
+pub fn run(args: SetLaunchArgs) -> Result<()> {
+    if !login::is_valid_login(&args.login) {
+        bail!("Invalid Twitch login: {}", args.login);
+    }
+
+    let key = args.login.to_lowercase();
+    let mut config = config::load_config()?;
+
+    if args.clear {
+        if config.launch_overrides.remove(&key).is_none() {
+            println!("No launch overrides set for {}.", key);
+            return Ok(());
+        }
+        config::save_config_default(&config)?;
+        println!("Cleared launch overrides for {}.", key);
+        return Ok(());
+    }
+
+    if args.quality.is_none() && args.player.is_none() && args.extra_args.is_empty() {
+        match config.launch_override(&key) {
+            Some(launch) => print_override(&key, launch),
+            None => println!("No launch overrides set for {}.", key),
+        }
+        return Ok(());
+    }
+
+    let launch = config.launch_overrides.entry(key.clone()).or_default();
+    if let Some(quality) = args.quality {
+        launch.quality = Some(quality);
+    }
+    if let Some(player) = args.player {
+        launch.player = Some(player);
+    }
+    if !args.extra_args.is_empty() {
+        launch.extra_args = args.extra_args;
+    }
+    let launch = launch.clone();
+
+    config::save_config_default(&config)?;
+    println!("Updated launch overrides for {}.", key);
+    print_override(&key, &launch);
+    Ok(())
+}
+
+fn print_override(login: &str, launch: &LaunchOverride) {
+    println!("{}:", login);
+    println!("  quality: {}", launch.quality.as_deref().unwrap_or("-"));
+    println!("  player: {}", launch.player.as_deref().unwrap_or("-"));
+    if launch.extra_args.is_empty() {
+        println!("  extra args: -");
+    } else {
+        println!("  extra args: {}", launch.extra_args.join(" "));
+    }
+}
@@ -1,10 +1,27 @@
-use std::collections::HashMap;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+#[cfg(feature = "thumbnails")]
+use std::io::IsTerminal;
+use std::io::{self, Write};
+use std::path::PathBuf;
 
-use anyhow::Result;
-use colored::Colorize;
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDateTime, SecondsFormat, Utc};
 use clap::{Args, ValueEnum};
+use colored::{ColoredString, Colorize};
+#[cfg(feature = "thumbnails")]
+use log::debug;
 
-use crate::{auth, config, db, twitch};
+use crate::output::{self, Align, OutputFormat};
+use crate::twitch::TwitchClient;
+use crate::{auth, config, db, fs_utils, prompt, twitch};
+
+const THUMBNAIL_WIDTH: u32 = 440;
+const THUMBNAIL_HEIGHT: u32 = 248;
+// Terminal columns an inline preview takes up.
+#[cfg(feature = "thumbnails")]
+const THUMBNAIL_COLUMNS: u32 = 40;
 
 #[derive(Debug, Clone, ValueEnum)]
 pub enum ListStatus {
@@ -13,46 +30,182 @@ pub enum ListStatus {
     All,
 }
 
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum SortKey {
+    /// Login name
+    Login,
+    /// Display name
+    Display,
+    /// Game or category
+    Game,
+    /// Viewer count, lowest first
+    Viewers,
+    /// Viewer count, highest first
+    ViewersDesc,
+    /// Time live, shortest first
+    Uptime,
+    /// Time live, longest first
+    UptimeDesc,
+}
+
 #[derive(Debug, Args)]
 #[command(about = "List followed streamers")]
 pub struct ListArgs {
     #[arg(long, value_enum, default_value_t = ListStatus::Online, help = "Filter by online status")]
     pub status: ListStatus,
+    #[arg(
+        long,
+        value_enum,
+        value_name = "KEY",
+        help = "Sort rows by the given key"
+    )]
+    pub sort: Option<SortKey>,
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Only show online streamers whose game contains NAME (case-insensitive)"
+    )]
+    pub game: Option<String>,
+    #[arg(
+        long,
+        value_name = "ID",
+        conflicts_with = "game",
+        value_parser = game_id,
+        help = "Only show online streamers whose game has this Twitch game ID"
+    )]
+    pub game_id: Option<String>,
+    #[arg(long, help = "Reverse the output order")]
+    pub reverse: bool,
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "Show at most N rows"
+    )]
+    pub limit: Option<u64>,
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 0,
+        help = "Skip the first N rows"
+    )]
+    pub offset: usize,
+    #[arg(
+        long,
+        help = "Offer to unfollow followed channels that no longer exist on Twitch"
+    )]
+    pub prune_missing: bool,
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Write the list to a file instead of stdout (without colors)"
+    )]
+    pub output: Option<PathBuf>,
+    #[arg(
+        long,
+        help = "Add a column with how long ago each streamer was followed"
+    )]
+    pub show_followed_since: bool,
+    #[arg(
+        long,
+        help = "Show a preview image for each online stream below the table"
+    )]
+    pub thumbnails: bool,
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table, help = "Output format")]
+    pub format: OutputFormat,
+}
+
+// Value parser for `--game-id`.
+fn game_id(value: &str) -> Result<String, String> {
+    let value = value.trim();
+    if value.is_empty() || !value.chars().all(|ch| ch.is_ascii_digit()) {
+        return Err("expected a numeric Twitch game ID, e.g. 509658".to_string());
+    }
+    Ok(value.to_string())
 }
 
 pub async fn run(args: ListArgs) -> Result<()> {
+    // Files get plain text; colors only make sense on a terminal.
+    if args.output.is_some() {
+        colored::control::set_override(false);
+    }
+
+    let mut out = Vec::new();
+    render(&args, &mut out).await?;
+
+    match &args.output {
+        Some(path) => {
+            if let Some(dir) = path.parent()
+                && !dir.as_os_str().is_empty()
+                && !dir.exists()
+            {
+                fs_utils::ensure_dir(dir)?;
+            }
+            fs::write(path, &out).with_context(|| format!("failed to write {}", path.display()))?;
+            eprintln!("Wrote list to {}.", path.display());
+        }
+        None => io::stdout()
+            .write_all(&out)
+            .context("failed to write list output")?,
+    }
+    Ok(())
+}
+
+async fn render(args: &ListArgs, out: &mut Vec<u8>) -> Result<()> {
     let pool = db::connect().await?;
-    let streamers = db::list_streamers(&pool).await?;
+    let mut streamers = db::list_streamers(&pool).await?;
     if streamers.is_empty() {
-        println!("No followed streamers.");
+        output::write_empty(out, args.format, "No followed streamers.")?;
         return Ok(());
     }
 
     let mut config = config::load_config()?;
     if config::token_needs_refresh(&config) {
-        auth::run(auth::AuthArgs {
-            show: false,
-            verbose: false,
-        })
-        .await?;
+        auth::run(auth::AuthArgs::default()).await?;
         config = config::load_config()?;
     }
 
-    let client_id = config::require_client_id(&config)?;
-    let access_token = config::require_access_token(&config)?;
+    let client = TwitchClient::from_config(&config)?;
+
+    if args.prune_missing {
+        let pruned = prune_missing(&pool, &client, &streamers).await?;
+        streamers.retain(|streamer| !pruned.contains(&streamer.id));
+        if streamers.is_empty() {
+            output::write_empty(out, args.format, "No followed streamers.")?;
+            return Ok(());
+        }
+    }
 
     let ids: Vec<String> = streamers
         .iter()
         .map(|streamer| streamer.id.clone())
         .collect();
-    let streams = twitch::fetch_streams_by_user_ids(client_id, access_token, &ids).await?;
+    let streams = client.fetch_streams_by_user_ids(&ids).await?;
+    let online_ids: Vec<String> = streams
+        .iter()
+        .map(|stream| stream.user_id.clone())
+        .collect();
+    let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    db::mark_streamers_online(&pool, &online_ids, &now).await?;
     let online_map: HashMap<String, twitch::TwitchStream> = streams
         .into_iter()
         .map(|stream| (stream.user_id.clone(), stream))
         .collect();
 
+    // Rows do not carry the game ID, so `--game-id` drops streamers up front.
+    // Offline streamers have no game and never match.
+    let playing_game_id = |streamer: &db::DbStreamer| match &args.game_id {
+        Some(id) => online_map
+            .get(&streamer.id)
+            .is_some_and(|stream| stream.game_id == *id),
+        None => true,
+    };
+
     let mut rows = Vec::new();
-    for streamer in &streamers {
+    for streamer in streamers
+        .iter()
+        .filter(|streamer| playing_game_id(streamer))
+    {
         let online = online_map.get(&streamer.id);
         match args.status {
             ListStatus::Online => {
@@ -74,24 +227,231 @@ pub async fn run(args: ListArgs) -> Result<()> {
         }
     }
 
+    if let Some(game) = &args.game {
+        let needle = game.to_lowercase();
+        rows.retain(|row| row.viewers.is_some() && row.game_name.to_lowercase().contains(&needle));
+        if rows.is_empty() {
+            output::write_empty(
+                out,
+                args.format,
+                &format!("No online streamers playing `{}`.", game),
+            )?;
+            return Ok(());
+        }
+    }
+
+    if let Some(id) = &args.game_id
+        && rows.is_empty()
+    {
+        let message = format!("No online streamers playing the game with ID {}.", id);
+        output::write_empty(out, args.format, &message)?;
+        return Ok(());
+    }
+
     if rows.is_empty() {
         match args.status {
-            ListStatus::Online => println!("No online streamers."),
-            ListStatus::Offline => println!("No offline streamers."),
-            ListStatus::All => println!("No streamers found."),
+            ListStatus::Online => output::write_empty(out, args.format, "No online streamers.")?,
+            ListStatus::Offline => output::write_empty(out, args.format, "No offline streamers.")?,
+            ListStatus::All => output::write_empty(out, args.format, "No streamers found.")?,
         }
         return Ok(());
     }
 
-    print_table(&rows, matches!(args.status, ListStatus::All));
+    match args.sort {
+        Some(key) => sort_rows(&mut rows, key),
+        // Online streamers first; the stable sort keeps them ordered by login.
+        None if matches!(args.status, ListStatus::All) => {
+            rows.sort_by_key(|row| row.viewers.is_none())
+        }
+        None => {}
+    }
+    if args.reverse {
+        rows.reverse();
+    }
+
+    let total = rows.len();
+    let paged = args.limit.is_some() || args.offset > 0;
+    let start = args.offset.min(total);
+    let end = args.limit.map_or(total, |limit| {
+        let limit = usize::try_from(limit).unwrap_or(usize::MAX);
+        start.saturating_add(limit).min(total)
+    });
+    if start == end {
+        let message = format!("No rows after offset {} ({} in total).", args.offset, total);
+        output::write_empty(out, args.format, &message)?;
+        return Ok(());
+    }
+
+    let columns = Columns {
+        status: matches!(args.status, ListStatus::All),
+        uptime: !matches!(args.status, ListStatus::Offline),
+        last_seen: !matches!(args.status, ListStatus::Online),
+        followed_since: args.show_followed_since,
+        ..Columns::default()
+    };
+    print_rows(
+        out,
+        args.format,
+        &rows[start..end],
+        columns,
+        config.list.offline_placeholder.as_deref(),
+    )?;
+    if args.format != OutputFormat::Table {
+        return Ok(());
+    }
+    if paged {
+        writeln!(out, "showing {}-{} of {}", start + 1, end, total)?;
+    }
+    if args.thumbnails {
+        let thumbnails = thumbnails(&rows[start..end], &online_map);
+        if thumbnails.is_empty() {
+            return Ok(());
+        }
+        #[cfg(feature = "thumbnails")]
+        if args.output.is_none()
+            && io::stdout().is_terminal()
+            && colored::control::SHOULD_COLORIZE.should_colorize()
+        {
+            // Images are drawn straight to the terminal, below the table.
+            io::stdout().write_all(out)?;
+            out.clear();
+            return show_thumbnails(&config, &thumbnails).await;
+        }
+        print_thumbnails(out, &thumbnails)?;
+    }
+    Ok(())
+}
+
+// Login and preview URL of each online stream among `rows`.
+fn thumbnails<'a>(
+    rows: &'a [Row],
+    streams: &HashMap<String, twitch::TwitchStream>,
+) -> Vec<(&'a str, String)> {
+    let by_login: HashMap<&str, &twitch::TwitchStream> = streams
+        .values()
+        .map(|stream| (stream.user_login.as_str(), stream))
+        .collect();
+    rows.iter()
+        .filter_map(|row| {
+            let stream = by_login.get(row.login.as_str())?;
+            (!stream.thumbnail_url.is_empty()).then(|| {
+                (
+                    row.login.as_str(),
+                    stream.thumbnail(THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT),
+                )
+            })
+        })
+        .collect()
+}
+
+fn print_thumbnails(out: &mut dyn Write, thumbnails: &[(&str, String)]) -> io::Result<()> {
+    let login_width = thumbnails
+        .iter()
+        .map(|(login, _)| login.len())
+        .max()
+        .unwrap_or(0);
+    writeln!(out)?;
+    for (login, url) in thumbnails {
+        writeln!(out, "{:<login_width$}  {}", login, url)?;
+    }
+    Ok(())
+}
+
+// Draws each preview below its login. A preview that cannot be downloaded or
+// drawn is printed as its URL instead.
+#[cfg(feature = "thumbnails")]
+async fn show_thumbnails(config: &config::Config, thumbnails: &[(&str, String)]) -> Result<()> {
+    let http = twitch::client(config)?;
+    let viewer = viuer::Config {
+        absolute_offset: false,
+        width: Some(THUMBNAIL_COLUMNS),
+        ..Default::default()
+    };
+    for (login, url) in thumbnails {
+        println!();
+        println!("{}", login.bold());
+        let drawn = match fetch_image(http, url).await {
+            Ok(image) => viuer::print(&image, &viewer).map_err(anyhow::Error::from),
+            Err(err) => Err(err),
+        };
+        if let Err(err) = drawn {
+            debug!("Could not show the preview for {login}: {err:#}");
+            println!("{url}");
+        }
+    }
     Ok(())
 }
 
-struct Row {
-    login: String,
-    display_name: String,
-    game_name: String,
-    status: Option<&'static str>,
+#[cfg(feature = "thumbnails")]
+async fn fetch_image(http: &reqwest::Client, url: &str) -> Result<image::DynamicImage> {
+    let bytes = http
+        .get(url)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .with_context(|| format!("failed to download {url}"))?
+        .bytes()
+        .await
+        .with_context(|| format!("failed to download {url}"))?;
+    image::load_from_memory(&bytes).with_context(|| format!("failed to decode {url}"))
+}
+
+// Only ids absent from a successful `/users` response count as missing; any
+// request failure aborts before anything is deleted.
+async fn prune_missing(
+    pool: &sqlx::SqlitePool,
+    client: &TwitchClient,
+    streamers: &[db::DbStreamer],
+) -> Result<HashSet<String>> {
+    let ids: Vec<String> = streamers
+        .iter()
+        .map(|streamer| streamer.id.clone())
+        .collect();
+    let users = client.fetch_users_by_id(&ids).await?;
+    let found: HashSet<&str> = users.iter().map(|user| user.id.as_str()).collect();
+    let missing: Vec<&db::DbStreamer> = streamers
+        .iter()
+        .filter(|streamer| !found.contains(streamer.id.as_str()))
+        .collect();
+
+    let mut pruned = HashSet::new();
+    if missing.is_empty() {
+        eprintln!("All followed channels still exist on Twitch.");
+        return Ok(pruned);
+    }
+
+    let names: Vec<&str> = missing
+        .iter()
+        .map(|streamer| streamer.name.as_str())
+        .collect();
+    eprintln!(
+        "{} followed channel(s) no longer exist on Twitch: {}",
+        missing.len(),
+        names.join(", ")
+    );
+    if !prompt::confirm("Unfollow them?")? {
+        eprintln!("Pruned 0 streamer(s).");
+        return Ok(pruned);
+    }
+
+    for streamer in missing {
+        if db::delete_streamer_by_id(pool, &streamer.id).await? > 0 {
+            pruned.insert(streamer.id.clone());
+        }
+    }
+    eprintln!("Pruned {} streamer(s).", pruned.len());
+    Ok(pruned)
+}
+
+pub(crate) struct Row {
+    pub(crate) login: String,
+    pub(crate) display_name: String,
+    pub(crate) game_name: String,
+    pub(crate) status: Option<&'static str>,
+    pub(crate) viewers: Option<u64>,
+    pub(crate) uptime_secs: Option<i64>,
+    pub(crate) last_online_at: Option<DateTime<Utc>>,
+    pub(crate) followed_at: Option<DateTime<Utc>>,
 }
 
 impl Row {
@@ -101,6 +461,10 @@ impl Row {
             display_name: streamer.display_name.clone(),
             game_name: stream.game_name.clone(),
             status: None,
+            viewers: Some(stream.viewer_count),
+            uptime_secs: Some(uptime_secs(stream)),
+            last_online_at: Some(Utc::now()),
+            followed_at: followed_at(streamer),
         }
     }
 
@@ -110,6 +474,10 @@ impl Row {
             display_name: streamer.display_name.clone(),
             game_name: String::new(),
             status: None,
+            viewers: None,
+            uptime_secs: None,
+            last_online_at: last_online_at(streamer),
+            followed_at: followed_at(streamer),
         }
     }
 
@@ -119,6 +487,10 @@ impl Row {
             display_name: streamer.display_name.clone(),
             game_name: stream.game_name.clone(),
             status: Some("online"),
+            viewers: Some(stream.viewer_count),
+            uptime_secs: Some(uptime_secs(stream)),
+            last_online_at: Some(Utc::now()),
+            followed_at: followed_at(streamer),
         }
     }
 
@@ -128,91 +500,249 @@ impl Row {
             display_name: streamer.display_name.clone(),
             game_name: String::new(),
             status: Some("offline"),
+            viewers: None,
+            uptime_secs: None,
+            last_online_at: last_online_at(streamer),
+            followed_at: followed_at(streamer),
         }
     }
 }
 
-fn print_table(rows: &[Row], include_status: bool) {
-    let login_width = rows
-        .iter()
-        .map(|row| row.login.len())
-        .max()
-        .unwrap_or(5)
-        .max("login".len());
-    let display_width = rows
-        .iter()
-        .map(|row| row.display_name.len())
-        .max()
-        .unwrap_or(12)
-        .max("display_name".len());
-    let game_width = rows
-        .iter()
-        .map(|row| row.game_name.len())
-        .max()
-        .unwrap_or(4)
-        .max("game".len());
-    let status_width = if include_status {
-        rows.iter()
-            .filter_map(|row| row.status.map(|status| status.len()))
-            .max()
-            .unwrap_or(6)
-            .max("status".len())
-    } else {
-        0
-    };
+fn last_online_at(streamer: &db::DbStreamer) -> Option<DateTime<Utc>> {
+    streamer
+        .last_online_at
+        .as_deref()
+        .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+        .map(|at| at.with_timezone(&Utc))
+}
 
-    let header_login = format!("{:<login_width$}", "login", login_width = login_width)
-        .cyan()
-        .bold();
-    let header_display = format!(
-        "{:<display_width$}",
-        "display_name",
-        display_width = display_width
-    )
-    .cyan()
-    .bold();
-    let header_game = format!("{:<game_width$}", "game", game_width = game_width)
-        .cyan()
-        .bold();
-    if include_status {
-        let header_status = format!("{:<status_width$}", "status", status_width = status_width)
-            .cyan()
-            .bold();
-        println!(
-            "{}  {}  {}  {}",
-            header_login, header_display, header_game, header_status
-        );
-    } else {
-        println!(
-            "{}  {}  {}",
-            header_login, header_display, header_game
+// `created_at` is filled in by SQLite's CURRENT_TIMESTAMP, which is UTC
+// without an offset.
+fn followed_at(streamer: &db::DbStreamer) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(&streamer.created_at, "%Y-%m-%d %H:%M:%S")
+        .ok()
+        .map(|at| at.and_utc())
+}
+
+fn uptime_secs(stream: &twitch::TwitchStream) -> i64 {
+    (Utc::now() - stream.started_at).num_seconds()
+}
+
+// Text keys compare case-insensitively. Rows without viewers or uptime
+// (offline) sort before online rows, or after them for the `-desc` keys.
+// Ties are broken by login so the order is deterministic.
+fn sort_rows(rows: &mut [Row], key: SortKey) {
+    rows.sort_by_cached_key(|row| row.login.to_lowercase());
+    match key {
+        SortKey::Login => {}
+        SortKey::Display => rows.sort_by_cached_key(|row| row.display_name.to_lowercase()),
+        SortKey::Game => rows.sort_by_cached_key(|row| row.game_name.to_lowercase()),
+        SortKey::Viewers => rows.sort_by_key(|row| row.viewers),
+        SortKey::ViewersDesc => rows.sort_by_key(|row| Reverse(row.viewers)),
+        SortKey::Uptime => rows.sort_by_key(|row| row.uptime_secs),
+        SortKey::UptimeDesc => rows.sort_by_key(|row| Reverse(row.uptime_secs)),
+    }
+}
+
+/// Optional columns after login, display name and game.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Columns {
+    pub(crate) uptime: bool,
+    pub(crate) viewers: bool,
+    pub(crate) status: bool,
+    pub(crate) last_seen: bool,
+    pub(crate) followed_since: bool,
+}
+
+/// Renders `rows` in `format`. JSON and plain output carry raw values (RFC3339
+/// timestamps instead of `3h ago`) and leave the game of offline rows empty.
+pub(crate) fn print_rows(
+    out: &mut dyn Write,
+    format: OutputFormat,
+    rows: &[Row],
+    columns: Columns,
+    offline_placeholder: Option<&str>,
+) -> io::Result<()> {
+    match format {
+        OutputFormat::Table => print_table(out, rows, columns, offline_placeholder),
+        OutputFormat::Json => {
+            let rows: Vec<serde_json::Value> =
+                rows.iter().map(|row| json_row(row, columns)).collect();
+            output::write_json(out, &rows)
+        }
+        OutputFormat::Plain => {
+            let rows: Vec<Vec<String>> = rows.iter().map(|row| plain_row(row, columns)).collect();
+            output::write_plain(out, &rows)
+        }
+    }
+}
+
+fn json_row(row: &Row, columns: Columns) -> serde_json::Value {
+    let mut object = serde_json::Map::new();
+    object.insert("login".into(), row.login.clone().into());
+    object.insert("display_name".into(), row.display_name.clone().into());
+    object.insert("game".into(), row.game_name.clone().into());
+    if columns.uptime {
+        object.insert("uptime_secs".into(), row.uptime_secs.into());
+    }
+    if columns.viewers {
+        object.insert("viewers".into(), row.viewers.into());
+    }
+    if columns.status {
+        object.insert("status".into(), row.status.into());
+    }
+    if columns.last_seen {
+        object.insert(
+            "last_online_at".into(),
+            row.last_online_at.map(timestamp).into(),
         );
     }
+    if columns.followed_since {
+        object.insert("followed_at".into(), row.followed_at.map(timestamp).into());
+    }
+    object.into()
+}
 
-    for row in rows {
-        let login = format!("{:<login_width$}", row.login, login_width = login_width).bold();
-        let display = format!(
-            "{:<display_width$}",
-            row.display_name,
-            display_width = display_width
+fn plain_row(row: &Row, columns: Columns) -> Vec<String> {
+    let mut fields = vec![
+        row.login.clone(),
+        row.display_name.clone(),
+        row.game_name.clone(),
+    ];
+    if columns.uptime {
+        fields.push(
+            row.uptime_secs
+                .map(|secs| secs.to_string())
+                .unwrap_or_default(),
         );
-        let game_value = format!("{:<game_width$}", row.game_name, game_width = game_width);
-        let game = if row.game_name.is_empty() {
-            game_value.normal()
-        } else {
-            game_value.yellow()
-        };
-        if include_status {
-            let status = row.status.unwrap_or("");
-            let status_padded = format!("{:<status_width$}", status, status_width = status_width);
-            let status_colored = match status {
-                "online" => status_padded.green(),
-                "offline" => status_padded.red(),
-                _ => status_padded.normal(),
+    }
+    if columns.viewers {
+        fields.push(
+            row.viewers
+                .map(|viewers| viewers.to_string())
+                .unwrap_or_default(),
+        );
+    }
+    if columns.status {
+        fields.push(row.status.unwrap_or("").to_string());
+    }
+    if columns.last_seen {
+        fields.push(row.last_online_at.map(timestamp).unwrap_or_default());
+    }
+    if columns.followed_since {
+        fields.push(row.followed_at.map(timestamp).unwrap_or_default());
+    }
+    fields
+}
+
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// `offline_placeholder` fills the game column of rows with an `offline`
+/// status.
+fn print_table(
+    out: &mut dyn Write,
+    rows: &[Row],
+    columns: Columns,
+    offline_placeholder: Option<&str>,
+) -> io::Result<()> {
+    let placeholder = offline_placeholder.unwrap_or("");
+    let mut header = vec![
+        ("login", Align::Left),
+        ("display_name", Align::Left),
+        ("game", Align::Left),
+    ];
+    if columns.uptime {
+        header.push(("uptime", Align::Right));
+    }
+    if columns.viewers {
+        header.push(("viewers", Align::Right));
+    }
+    if columns.status {
+        header.push(("status", Align::Left));
+    }
+    if columns.last_seen {
+        header.push(("last_seen", Align::Left));
+    }
+    if columns.followed_since {
+        header.push(("followed", Align::Left));
+    }
+
+    let cells: Vec<Vec<ColoredString>> = rows
+        .iter()
+        .map(|row| {
+            let game = if row.game_name.is_empty() {
+                if row.status == Some("offline") {
+                    placeholder.normal()
+                } else {
+                    "".normal()
+                }
+            } else {
+                row.game_name.as_str().yellow()
             };
-            println!("{}  {}  {}  {}", login, display, game, status_colored);
-        } else {
-            println!("{}  {}  {}", login, display, game);
-        }
+            let mut cells = vec![
+                row.login.as_str().bold(),
+                row.display_name.as_str().normal(),
+                game,
+            ];
+            if columns.uptime {
+                let uptime = row.uptime_secs.map(format_uptime).unwrap_or_default();
+                cells.push(uptime.normal());
+            }
+            if columns.viewers {
+                let viewers = row
+                    .viewers
+                    .map(|viewers| viewers.to_string())
+                    .unwrap_or_default();
+                cells.push(viewers.normal());
+            }
+            if columns.status {
+                cells.push(match row.status.unwrap_or("") {
+                    "online" => "online".green(),
+                    "offline" => "offline".red(),
+                    status => status.normal(),
+                });
+            }
+            if columns.last_seen {
+                let last_seen = if row.viewers.is_some() {
+                    "now".to_string()
+                } else {
+                    row.last_online_at
+                        .map(|at| humanize_ago((Utc::now() - at).num_seconds()))
+                        .unwrap_or_else(|| "never".to_string())
+                };
+                cells.push(last_seen.dimmed());
+            }
+            if columns.followed_since {
+                let followed = row
+                    .followed_at
+                    .map(|at| humanize_ago((Utc::now() - at).num_seconds()))
+                    .unwrap_or_default();
+                cells.push(followed.dimmed());
+            }
+            cells
+        })
+        .collect();
+    output::write_table(out, &header, &cells)
+}
+
+// `2h13m`, or only minutes below an hour.
+fn format_uptime(secs: i64) -> String {
+    let minutes = secs.max(0) / 60;
+    if minutes < 60 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}h{:02}m", minutes / 60, minutes % 60)
+    }
+}
+
+fn humanize_ago(secs: i64) -> String {
+    let secs = secs.max(0);
+    match secs {
+        0..60 => "just now".to_string(),
+        60..3600 => format!("{}m ago", secs / 60),
+        3600..86400 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86400),
     }
 }
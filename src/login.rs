@@ -0,0 +1,41 @@
+use std::collections::HashSet;
+
+/// Twitch logins are ASCII letters, digits, and underscores.
+pub fn is_valid_login(login: &str) -> bool {
+    !login.is_empty()
+        && login
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+}
+
+/// Drops repeated logins, ignoring case, and keeps the first spelling of each.
+pub fn dedup_logins(logins: Vec<String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    logins
+        .into_iter()
+        .filter(|login| seen.insert(login.to_lowercase()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_valid_login_accepts_twitch_logins_only() {
+        assert!(is_valid_login("jonhoo"));
+        assert!(is_valid_login("The_Primeagen42"));
+        assert!(!is_valid_login(""));
+        assert!(!is_valid_login("not-a-login"));
+        assert!(!is_valid_login("@jonhoo"));
+        assert!(!is_valid_login("jönhoo"));
+    }
+
+    #[test]
+    fn dedup_logins_keeps_the_first_spelling() {
+        let logins = ["Jonhoo", "shroud", "jonhoo", "JONHOO"]
+            .map(String::from)
+            .to_vec();
+        assert_eq!(dedup_logins(logins), ["Jonhoo", "shroud"]);
+    }
+}
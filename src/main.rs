@@ -1,18 +1,39 @@
+use std::io::Write;
+
 use anyhow::Result;
 use clap::{Parser, Subcommand};
 
 mod auth;
+mod cache;
+mod clips;
 mod config;
 mod db;
+mod error;
+mod export;
 mod follow;
 mod fs_utils;
+mod games;
+mod history;
+mod info;
+mod launch;
 mod list;
+mod login;
+mod notify;
+mod output;
 mod paths;
+mod prompt;
+mod quality;
+mod refresh;
+mod retry;
+mod search;
+mod status;
 mod streamlink;
+mod template;
+mod top;
 mod twitch;
 mod unfollow;
-mod watch;
 mod vod;
+mod watch;
 
 #[derive(Debug, Parser)]
 #[command(
@@ -24,6 +45,43 @@ mod vod;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    #[arg(
+        long,
+        global = true,
+        help = "Disable colored output (NO_COLOR is honored too)"
+    )]
+    no_color: bool,
+    #[arg(
+        short,
+        long,
+        global = true,
+        help = "Print diagnostic details on stderr (RUST_LOG takes precedence)"
+    )]
+    verbose: bool,
+    #[arg(
+        short,
+        long,
+        global = true,
+        help = "Answer yes to all confirmations (required for destructive commands in scripts)"
+    )]
+    yes: bool,
+    #[arg(
+        long,
+        global = true,
+        value_name = "NAME",
+        help = "Use a separate config and database for this profile"
+    )]
+    profile: Option<String>,
+    #[arg(long, global = true, help = "Print errors as JSON on stderr")]
+    json_errors: bool,
+    #[arg(
+        long,
+        global = true,
+        value_name = "SECS",
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "Timeout for each Twitch API request (overrides the config)"
+    )]
+    timeout: Option<u64>,
 }
 
 #[derive(Debug, Subcommand)]
@@ -35,12 +93,75 @@ enum Commands {
     Unfollow(unfollow::UnfollowArgs),
     Watch(watch::WatchArgs),
     Vod(vod::VodArgs),
+    Search(search::SearchArgs),
+    Db(db::DbArgs),
+    Export(export::ExportArgs),
+    SetLaunch(launch::SetLaunchArgs),
+    History(history::HistoryArgs),
+    Notify(notify::NotifyArgs),
+    Clips(clips::ClipsArgs),
+    Status(status::StatusArgs),
+    Info(info::InfoArgs),
+    Top(top::TopArgs),
+    Games(games::GamesArgs),
+    Refresh(refresh::RefreshArgs),
+    Quality(quality::QualityArgs),
 }
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() {
     let cli = Cli::parse();
-    match cli.command {
+    init_logging(cli.verbose);
+    if cli.no_color {
+        colored::control::set_override(false);
+    }
+    if cli.yes {
+        prompt::set_assume_yes();
+    }
+    if let Some(secs) = cli.timeout {
+        twitch::set_request_timeout(secs);
+    }
+    let json_errors =
+        cli.json_errors || matches!(&cli.command, Commands::Notify(args) if args.json);
+
+    // Errors are printed like anyhow's default, but the exit code depends
+    // on the kind of error so that scripts can tell failures apart.
+    if let Err(err) = run(cli.command, cli.profile.as_deref()).await {
+        if json_errors {
+            error::print_json(&err);
+        } else {
+            eprintln!("Error: {err:?}");
+        }
+        std::process::exit(error::ErrorKind::of(&err).exit_code());
+    }
+}
+
+// Warnings are always shown; `--verbose` adds this crate's info and debug
+// messages. `RUST_LOG` replaces both, e.g. `RUST_LOG=debug` for all crates.
+fn init_logging(verbose: bool) {
+    let level = if verbose {
+        log::LevelFilter::Debug
+    } else {
+        log::LevelFilter::Warn
+    };
+    let mut builder = env_logger::Builder::new();
+    if std::env::var_os("RUST_LOG").is_some() {
+        builder.parse_default_env();
+    } else {
+        builder
+            .filter_level(log::LevelFilter::Warn)
+            .filter_module("ttv", level);
+    }
+    builder
+        .format(|buf, record| writeln!(buf, "[{}] {}", record.level(), record.args()))
+        .init();
+}
+
+async fn run(command: Commands, profile: Option<&str>) -> Result<()> {
+    if let Some(profile) = profile {
+        paths::set_profile(profile)?;
+    }
+    match command {
         Commands::Config(args) => config::run(args),
         Commands::Auth(args) => auth::run(args).await,
         Commands::Follow(args) => follow::run(args).await,
@@ -48,5 +169,18 @@ async fn main() -> Result<()> {
         Commands::Unfollow(args) => unfollow::run(args).await,
         Commands::Watch(args) => watch::run(args).await,
         Commands::Vod(args) => vod::run(args).await,
+        Commands::Search(args) => search::run(args).await,
+        Commands::Db(args) => db::run(args).await,
+        Commands::Export(args) => export::run(args).await,
+        Commands::SetLaunch(args) => launch::run(args),
+        Commands::History(args) => history::run(args).await,
+        Commands::Notify(args) => notify::run(args).await,
+        Commands::Clips(args) => clips::run(args).await,
+        Commands::Status(args) => status::run(args).await,
+        Commands::Info(args) => info::run(args).await,
+        Commands::Top(args) => top::run(args).await,
+        Commands::Games(args) => games::run(args).await,
+        Commands::Refresh(args) => refresh::run(args).await,
+        Commands::Quality(args) => quality::run(args).await,
     }
 }
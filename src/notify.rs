@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+use std::sync::Once;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{SecondsFormat, Utc};
+use clap::Args;
+use log::warn;
+use serde::Serialize;
+
+use crate::twitch::TwitchClient;
+use crate::{auth, config, db, template, twitch};
+
+const DEFAULT_TITLE_TEMPLATE: &str = "{display_name} is live";
+const DEFAULT_BODY_TEMPLATE: &str = "Playing {game}: {title}";
+
+#[derive(Debug, Args)]
+#[command(about = "Report when followed streamers go live or offline")]
+pub struct NotifyArgs {
+    #[arg(
+        long,
+        value_name = "SECS",
+        default_value_t = 60,
+        value_parser = clap::value_parser!(u64).range(10..),
+        help = "Seconds between live checks"
+    )]
+    pub interval: u64,
+    #[arg(long, help = "Print events as newline-delimited JSON")]
+    pub json: bool,
+    #[arg(
+        long,
+        help = "Also show a desktop notification when a streamer goes live"
+    )]
+    pub desktop: bool,
+    #[arg(
+        long,
+        help = "Check once, report every streamer that is live, and exit"
+    )]
+    pub once: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum EventKind {
+    Online,
+    Offline,
+}
+
+#[derive(Debug, Serialize)]
+struct Event {
+    event: EventKind,
+    login: String,
+    display_name: String,
+    game: Option<String>,
+    title: Option<String>,
+    viewers: Option<u64>,
+    at: String,
+}
+
+struct Templates {
+    title: String,
+    body: String,
+}
+
+pub async fn run(args: NotifyArgs) -> Result<()> {
+    let config = config::load_config()?;
+    let templates = Templates {
+        title: config
+            .notify
+            .title_template
+            .unwrap_or_else(|| DEFAULT_TITLE_TEMPLATE.to_string()),
+        body: config
+            .notify
+            .body_template
+            .unwrap_or_else(|| DEFAULT_BODY_TEMPLATE.to_string()),
+    };
+    let pool = db::connect().await?;
+    let interval = Duration::from_secs(args.interval);
+
+    if args.once {
+        let (streamers, current) = poll(&pool).await?;
+        for event in transitions(&streamers, &HashMap::new(), &current) {
+            emit(&event, &templates, &args)?;
+        }
+        return Ok(());
+    }
+
+    // The first successful poll only records who is live; events are emitted
+    // for changes after that.
+    let mut previous: Option<HashMap<String, twitch::TwitchStream>> = None;
+    loop {
+        match poll(&pool).await {
+            Ok((streamers, current)) => {
+                if let Some(previous) = &previous {
+                    for event in transitions(&streamers, previous, &current) {
+                        emit(&event, &templates, &args)?;
+                    }
+                }
+                previous = Some(current);
+            }
+            Err(err) => warn!("Failed to check live streams: {err:#}"),
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        }
+    }
+}
+
+async fn poll(
+    pool: &sqlx::SqlitePool,
+) -> Result<(Vec<db::DbStreamer>, HashMap<String, twitch::TwitchStream>)> {
+    let mut config = config::load_config()?;
+    if config::token_needs_refresh(&config) {
+        auth::run(auth::AuthArgs::default()).await?;
+        config = config::load_config()?;
+    }
+
+    let client = TwitchClient::from_config(&config)?;
+
+    let streamers = db::list_streamers(pool).await?;
+    let ids: Vec<String> = streamers
+        .iter()
+        .map(|streamer| streamer.id.clone())
+        .collect();
+    let streams = client.fetch_streams_by_user_ids(&ids).await?;
+    let online_ids: Vec<String> = streams
+        .iter()
+        .map(|stream| stream.user_id.clone())
+        .collect();
+    let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    db::mark_streamers_online(pool, &online_ids, &now).await?;
+    let current = streams
+        .into_iter()
+        .map(|stream| (stream.user_id.clone(), stream))
+        .collect();
+    Ok((streamers, current))
+}
+
+fn transitions(
+    streamers: &[db::DbStreamer],
+    previous: &HashMap<String, twitch::TwitchStream>,
+    current: &HashMap<String, twitch::TwitchStream>,
+) -> Vec<Event> {
+    let at = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let mut events = Vec::new();
+    for streamer in streamers {
+        let kind = match (previous.get(&streamer.id), current.get(&streamer.id)) {
+            (None, Some(_)) => EventKind::Online,
+            (Some(_), None) => EventKind::Offline,
+            _ => continue,
+        };
+        let stream = current.get(&streamer.id);
+        events.push(Event {
+            event: kind,
+            login: streamer.name.clone(),
+            display_name: streamer.display_name.clone(),
+            game: stream
+                .map(|stream| stream.game_name.clone())
+                .filter(|game| !game.is_empty()),
+            title: stream.map(|stream| stream.title.clone()),
+            viewers: stream.map(|stream| stream.viewer_count),
+            at: at.clone(),
+        });
+    }
+    events
+}
+
+fn emit(event: &Event, templates: &Templates, args: &NotifyArgs) -> Result<()> {
+    if args.desktop && event.event == EventKind::Online {
+        let (title, body) = render(event, templates);
+        send_desktop(&title, &body);
+    }
+
+    if args.json {
+        let line = serde_json::to_string(event).context("failed to serialize event")?;
+        println!("{line}");
+        return Ok(());
+    }
+
+    match event.event {
+        EventKind::Online => {
+            let (title, body) = render(event, templates);
+            println!("{} — {}", title, body);
+        }
+        EventKind::Offline => println!("{} went offline", event.display_name),
+    }
+    Ok(())
+}
+
+// Failing to show a desktop notification is not fatal; the event is still
+// printed.
+#[cfg(feature = "desktop-notifications")]
+fn send_desktop(title: &str, body: &str) {
+    let result = notify_rust::Notification::new()
+        .appname("ttv")
+        .summary(title)
+        .body(body)
+        .show();
+    if let Err(err) = result {
+        warn_once(&format!("Could not show a desktop notification: {err}"));
+    }
+}
+
+#[cfg(not(feature = "desktop-notifications"))]
+fn send_desktop(_title: &str, _body: &str) {
+    warn_once(
+        "This build of ttv has no desktop notification support. Rebuild it with `--features desktop-notifications`.",
+    );
+}
+
+fn warn_once(message: &str) {
+    static WARN: Once = Once::new();
+    WARN.call_once(|| warn!("{}", message));
+}
+
+fn render(event: &Event, templates: &Templates) -> (String, String) {
+    let viewers = event
+        .viewers
+        .map(|viewers| viewers.to_string())
+        .unwrap_or_default();
+    let values = [
+        ("login", event.login.as_str()),
+        ("display_name", event.display_name.as_str()),
+        ("game", event.game.as_deref().unwrap_or("")),
+        ("title", event.title.as_deref().unwrap_or("")),
+        ("viewers", viewers.as_str()),
+    ];
+    (
+        template::render(&templates.title, &values),
+        template::render(&templates.body, &values),
+    )
+}
@@ -0,0 +1,152 @@
+use std::io::{self, Write};
+
+use clap::ValueEnum;
+use colored::{ColoredString, Colorize};
+use serde::Serialize;
+use unicode_width::UnicodeWidthStr;
+
+/// Output format shared by the listing commands.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Aligned columns with a header, colored on a terminal
+    #[default]
+    Table,
+    /// A JSON array with one object per row
+    Json,
+    /// One tab-separated line per row, without header or colors
+    Plain,
+}
+
+/// Writes `rows` as a pretty-printed JSON array.
+pub fn write_json<T: Serialize>(out: &mut dyn Write, rows: &[T]) -> io::Result<()> {
+    serde_json::to_writer_pretty(&mut *out, rows)?;
+    writeln!(out)
+}
+
+/// Writes one line per row with the fields separated by tabs. Tabs and line
+/// breaks inside a field become spaces so every row stays on one line.
+pub fn write_plain(out: &mut dyn Write, rows: &[Vec<String>]) -> io::Result<()> {
+    for row in rows {
+        let fields: Vec<String> = row
+            .iter()
+            .map(|field| field.replace(['\t', '\n', '\r'], " "))
+            .collect();
+        writeln!(out, "{}", fields.join("\t"))?;
+    }
+    Ok(())
+}
+
+/// What to print when there is nothing to list: `message` for tables, an
+/// empty array for JSON, and nothing for plain output.
+pub fn write_empty(out: &mut dyn Write, format: OutputFormat, message: &str) -> io::Result<()> {
+    match format {
+        OutputFormat::Table => writeln!(out, "{message}"),
+        OutputFormat::Json => writeln!(out, "[]"),
+        OutputFormat::Plain => Ok(()),
+    }
+}
+
+/// Side of its column a table cell is aligned to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+/// Writes the table shared by the listing commands: a cyan header, then one
+/// line per row with the columns separated by two spaces. Every column is as
+/// wide as its widest cell or header, measured by display width, and cells
+/// keep their colors and styles. The last column is not padded, so long
+/// values such as titles do not widen the others.
+pub fn write_table(
+    out: &mut dyn Write,
+    columns: &[(&str, Align)],
+    rows: &[Vec<ColoredString>],
+) -> io::Result<()> {
+    let widths: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(idx, (header, _))| {
+            rows.iter()
+                .filter_map(|row| row.get(idx))
+                .map(|cell| cell.width())
+                .fold(header.width(), usize::max)
+        })
+        .collect();
+    let line = |cells: Vec<ColoredString>| -> String {
+        let last = cells.len().saturating_sub(1);
+        cells
+            .into_iter()
+            .enumerate()
+            .map(|(idx, mut cell)| {
+                let (_, align) = columns[idx];
+                if idx < last || align == Align::Right {
+                    cell.input = pad_display(&cell.input, widths[idx], align);
+                }
+                cell.to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("  ")
+    };
+
+    let header = columns
+        .iter()
+        .map(|(header, _)| header.cyan().bold())
+        .collect();
+    writeln!(out, "{}", line(header))?;
+    for row in rows {
+        writeln!(out, "{}", line(row.clone()))?;
+    }
+    Ok(())
+}
+
+// Aligns `value` in `width` terminal columns. `format!` pads by char count,
+// which misaligns wide (CJK, emoji) and zero-width characters.
+fn pad_display(value: &str, width: usize, align: Align) -> String {
+    let padding = " ".repeat(width.saturating_sub(value.width()));
+    match align {
+        Align::Left => format!("{value}{padding}"),
+        Align::Right => format!("{padding}{value}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pad_display_aligns_wide_characters() {
+        // Each of these CJK characters takes two terminal columns.
+        let cjk = pad_display("日本語", 10, Align::Left);
+        let ascii = pad_display("abcdef", 10, Align::Left);
+        assert_eq!(cjk, "日本語    ");
+        assert_eq!(cjk.width(), 10);
+        assert_eq!(ascii.width(), cjk.width());
+        assert_eq!(pad_display("日本語", 8, Align::Right), "  日本語");
+    }
+
+    #[test]
+    fn pad_display_leaves_wider_values_alone() {
+        assert_eq!(pad_display("日本語", 4, Align::Left), "日本語");
+    }
+
+    #[test]
+    fn write_table_aligns_columns_by_display_width() {
+        colored::control::set_override(false);
+        let columns = [
+            ("#", Align::Right),
+            ("name", Align::Left),
+            ("id", Align::Left),
+        ];
+        let rows = vec![
+            vec!["1".normal(), "日本語".normal(), "1".normal()],
+            vec!["10".normal(), "Just Chatting".normal(), "509658".normal()],
+        ];
+        let mut out = Vec::new();
+        write_table(&mut out, &columns, &rows).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            " #  name           id\n 1  日本語         1\n10  Just Chatting  509658\n"
+        );
+    }
+}
@@ -1,9 +1,46 @@
 use std::env;
 use std::path::PathBuf;
+use std::sync::{Once, OnceLock};
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
+use log::warn;
 
+static PROFILE: OnceLock<String> = OnceLock::new();
+
+/// Selects a named profile for the rest of the run. Profiles keep their own
+/// config and database files next to the default ones.
+pub fn set_profile(name: &str) -> Result<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_')
+    {
+        bail!("Invalid profile name `{name}`. Use letters, digits, `-`, and `_`.");
+    }
+    PROFILE
+        .set(name.to_string())
+        .map_err(|_| anyhow::anyhow!("profile is already set"))
+}
+
+/// `<stem>.<ext>`, or `<stem>.<profile>.<ext>` with a profile selected.
+pub fn profile_file_name(stem: &str, ext: &str) -> String {
+    match PROFILE.get() {
+        Some(profile) => format!("{stem}.{profile}.{ext}"),
+        None => format!("{stem}.{ext}"),
+    }
+}
+
+// Lookup order for both directories:
+// 1. `TTV_HOME`, used as-is for config and data
+// 2. `XDG_CONFIG_HOME` / `XDG_DATA_HOME`
+// 3. `APPDATA` (Windows)
+// 4. `HOME` / `USERPROFILE` with the XDG default suffix
+// 5. `.ttv` in the current directory, with a warning
 pub fn config_dir() -> Result<PathBuf> {
+    if let Some(dir) = ttv_home() {
+        return Ok(dir);
+    }
+
     if let Ok(xdg) = env::var("XDG_CONFIG_HOME") {
         return Ok(PathBuf::from(xdg).join("ttv"));
     }
@@ -15,13 +52,17 @@ pub fn config_dir() -> Result<PathBuf> {
         }
     }
 
-    let home = env::var("HOME")
-        .or_else(|_| env::var("USERPROFILE"))
-        .context("could not determine home directory")?;
-    Ok(PathBuf::from(home).join(".config").join("ttv"))
+    match home_dir() {
+        Some(home) => Ok(home.join(".config").join("ttv")),
+        None => fallback_dir(),
+    }
 }
 
 pub fn data_dir() -> Result<PathBuf> {
+    if let Some(dir) = ttv_home() {
+        return Ok(dir);
+    }
+
     if let Ok(xdg) = env::var("XDG_DATA_HOME") {
         return Ok(PathBuf::from(xdg).join("ttv"));
     }
@@ -33,8 +74,66 @@ pub fn data_dir() -> Result<PathBuf> {
         }
     }
 
-    let home = env::var("HOME")
+    match home_dir() {
+        Some(home) => Ok(home.join(".local").join("share").join("ttv")),
+        None => fallback_dir(),
+    }
+}
+
+// Transient data that can be deleted at any time. Lookup order:
+// 1. `TTV_HOME/cache`
+// 2. `XDG_CACHE_HOME`
+// 3. `LOCALAPPDATA` (Windows), as `ttv\cache`
+// 4. `HOME` / `USERPROFILE` with `.cache`
+// 5. `.ttv/cache` in the current directory, with a warning
+pub fn cache_dir() -> Result<PathBuf> {
+    if let Some(dir) = ttv_home() {
+        return Ok(dir.join("cache"));
+    }
+
+    if let Ok(xdg) = env::var("XDG_CACHE_HOME") {
+        return Ok(PathBuf::from(xdg).join("ttv"));
+    }
+
+    #[cfg(windows)]
+    {
+        if let Ok(local) = env::var("LOCALAPPDATA") {
+            return Ok(PathBuf::from(local).join("ttv").join("cache"));
+        }
+    }
+
+    match home_dir() {
+        Some(home) => Ok(home.join(".cache").join("ttv")),
+        None => Ok(fallback_dir()?.join("cache")),
+    }
+}
+
+fn ttv_home() -> Option<PathBuf> {
+    env::var("TTV_HOME")
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .map(PathBuf::from)
+}
+
+fn home_dir() -> Option<PathBuf> {
+    env::var("HOME")
         .or_else(|_| env::var("USERPROFILE"))
-        .context("could not determine home directory")?;
-    Ok(PathBuf::from(home).join(".local").join("share").join("ttv"))
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .map(PathBuf::from)
+}
+
+fn fallback_dir() -> Result<PathBuf> {
+    let dir = env::current_dir()
+        .context("could not determine home directory or current directory")?
+        .join(".ttv");
+
+    static WARN: Once = Once::new();
+    WARN.call_once(|| {
+        warn!(
+            "Could not determine home directory; using {}. Set TTV_HOME to choose a location.",
+            dir.display()
+        );
+    });
+    Ok(dir)
 }
@@ -0,0 +1,84 @@
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use anyhow::{Context, Result, bail};
+use dialoguer::FuzzySelect;
+use dialoguer::theme::ColorfulTheme;
+
+static ASSUME_YES: AtomicBool = AtomicBool::new(false);
+
+/// Answers every later `confirm` with yes, for the global `--yes` flag.
+pub fn set_assume_yes() {
+    ASSUME_YES.store(true, Ordering::Relaxed);
+}
+
+pub fn assume_yes() -> bool {
+    ASSUME_YES.load(Ordering::Relaxed)
+}
+
+/// Asks a yes/no question on stdin. Anything but `y`/`yes` counts as no,
+/// and so does a non-interactive stdin unless `--yes` was given. The
+/// question goes to stderr, like `select`'s prompt.
+pub fn confirm(question: &str) -> Result<bool> {
+    if assume_yes() {
+        return Ok(true);
+    }
+    if !io::stdin().is_terminal() {
+        return Ok(false);
+    }
+
+    eprint!("{} [y/N]: ", question);
+    io::stderr().flush().ok();
+
+    let mut input = String::new();
+    io::stdin()
+        .read_line(&mut input)
+        .context("failed to read input")?;
+
+    let answer = input.trim().to_lowercase();
+    Ok(answer == "y" || answer == "yes")
+}
+
+/// Prompts for a number between 1 and `max` until a valid one is entered.
+/// An empty answer aborts. The prompt goes to stderr, like the fuzzy
+/// finder's, so that stdout only carries the command's output.
+pub fn select(item: &str, max: usize) -> Result<usize> {
+    loop {
+        eprint!("Select a {} (1-{}): ", item, max);
+        io::stderr().flush().ok();
+
+        let mut input = String::new();
+        io::stdin()
+            .read_line(&mut input)
+            .context("failed to read input")?;
+
+        let trimmed = input.trim();
+        if trimmed.is_empty() {
+            bail!("No selection provided.");
+        }
+
+        match trimmed.parse::<usize>() {
+            Ok(value) if (1..=max).contains(&value) => return Ok(value),
+            _ => eprintln!(
+                "Invalid selection. Please enter a number between 1 and {}.",
+                max
+            ),
+        }
+    }
+}
+
+/// Lets the user choose one of `labels` with a fuzzy finder that filters
+/// them as they type. Escape aborts. Returns a number from 1 to
+/// `labels.len()`.
+pub fn pick(item: &str, labels: &[String]) -> Result<usize> {
+    let selection = FuzzySelect::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!("Select a {} (type to filter)", item))
+        .items(labels)
+        .default(0)
+        .interact_opt()
+        .context("failed to read selection")?;
+    match selection {
+        Some(idx) => Ok(idx + 1),
+        None => bail!("No selection provided."),
+    }
+}
@@ -0,0 +1,59 @@
+use anyhow::{Result, bail};
+use clap::Args;
+
+use crate::error::NotFoundError;
+use crate::{db, login, streamlink};
+
+#[derive(Debug, Args)]
+#[command(about = "Set the stream quality watch uses for a followed streamer")]
+pub struct QualityArgs {
+    #[arg(
+        value_name = "LOGIN",
+        help = "Twitch login name of a followed streamer"
+    )]
+    pub login: String,
+    #[arg(
+        value_name = "QUALITY",
+        value_parser = streamlink::parse_quality,
+        help = "Stream quality, e.g. best, 720p60, audio_only, or a fallback list like 1080p60,720p,best"
+    )]
+    pub quality: Option<String>,
+    #[arg(long, conflicts_with = "quality", help = "Remove the stored quality")]
+    pub clear: bool,
+}
+
+pub async fn run(args: QualityArgs) -> Result<()> {
+    if !login::is_valid_login(&args.login) {
+        bail!("Invalid Twitch login: {}", args.login);
+    }
+
+    let login = args.login.to_lowercase();
+    let pool = db::connect().await?;
+
+    if !args.clear && args.quality.is_none() {
+        if !db::is_followed(&pool, &login).await? {
+            return Err(NotFoundError::error(format!(
+                "`{}` is not followed. Run `ttv follow {}` first.",
+                login, login
+            )));
+        }
+        match db::preferred_quality(&pool, &login).await? {
+            Some(quality) => println!("{}: {}", login, quality),
+            None => println!("No quality set for {}.", login),
+        }
+        return Ok(());
+    }
+
+    let quality = args.quality.as_deref();
+    if db::set_preferred_quality(&pool, &login, quality).await? == 0 {
+        return Err(NotFoundError::error(format!(
+            "`{}` is not followed. Run `ttv follow {}` first.",
+            login, login
+        )));
+    }
+    match quality {
+        Some(quality) => println!("Set quality for {} to {}.", login, quality),
+        None => println!("Cleared quality for {}.", login),
+    }
+    Ok(())
+}
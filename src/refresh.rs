@@ -0,0 +1,80 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+use chrono::{SecondsFormat, Utc};
+use clap::Args;
+use log::info;
+
+use crate::twitch::TwitchClient;
+use crate::{auth, config, db};
+
+#[derive(Debug, Args)]
+#[command(about = "Update followed streamers' logins and display names from Twitch")]
+pub struct RefreshArgs {}
+
+pub async fn run(_args: RefreshArgs) -> Result<()> {
+    let pool = db::connect().await?;
+    let streamers = db::list_streamers(&pool).await?;
+    if streamers.is_empty() {
+        println!("No followed streamers.");
+        return Ok(());
+    }
+
+    let mut config = config::load_config()?;
+    if config::token_needs_refresh(&config) {
+        auth::run(auth::AuthArgs::default()).await?;
+        config = config::load_config()?;
+    }
+
+    let client = TwitchClient::from_config(&config)?;
+
+    let ids: Vec<String> = streamers
+        .iter()
+        .map(|streamer| streamer.id.clone())
+        .collect();
+    let users = client.fetch_users_by_id(&ids).await?;
+    let by_id: HashMap<&str, &db::DbStreamer> = streamers
+        .iter()
+        .map(|streamer| (streamer.id.as_str(), streamer))
+        .collect();
+
+    let mut renamed = 0;
+    for user in &users {
+        let Some(stored) = by_id.get(user.id.as_str()) else {
+            continue;
+        };
+        if stored.name != user.login {
+            println!("Renamed {} -> {}", stored.name, user.login);
+            renamed += 1;
+        } else if stored.display_name != user.display_name {
+            info!(
+                "Display name of {} changed: {} -> {}",
+                user.login, stored.display_name, user.display_name
+            );
+        }
+        db::upsert_streamer(&pool, user).await?;
+    }
+    // Keep login lookups from resolving a streamer's old name.
+    let refreshed_at = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    db::cache_users(&pool, &users, &refreshed_at).await?;
+
+    let found: HashSet<&str> = users.iter().map(|user| user.id.as_str()).collect();
+    let missing: Vec<&str> = streamers
+        .iter()
+        .filter(|streamer| !found.contains(streamer.id.as_str()))
+        .map(|streamer| streamer.name.as_str())
+        .collect();
+    if !missing.is_empty() {
+        eprintln!(
+            "Not found on Twitch: {}. Run `ttv list --prune-missing` to unfollow them.",
+            missing.join(", ")
+        );
+    }
+
+    println!(
+        "Refreshed {} streamer(s), {} renamed.",
+        users.len(),
+        renamed
+    );
+    Ok(())
+}
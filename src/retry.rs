@@ -0,0 +1,84 @@
+//! Retry/backoff settings shared by the Twitch HTTP calls and the database.
+//!
+//! The values are read once from the environment:
+//!
+//! - `TTV_RETRY_MAX_ATTEMPTS`: total attempts, including the first (default 3, minimum 1)
+//! - `TTV_RETRY_BASE_DELAY_MS`: delay before the first retry, doubled per attempt (default 500)
+//! - `TTV_RETRY_MAX_DELAY_MS`: upper bound for a single delay (default 5000)
+
+use std::env;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use reqwest::StatusCode;
+
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+const DEFAULT_BASE_DELAY_MS: u64 = 500;
+const DEFAULT_MAX_DELAY_MS: u64 = 5_000;
+
+#[derive(Debug, Clone, Copy)]
+pub struct RetryConfig {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            base_delay: Duration::from_millis(DEFAULT_BASE_DELAY_MS),
+            max_delay: Duration::from_millis(DEFAULT_MAX_DELAY_MS),
+        }
+    }
+}
+
+impl RetryConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let max_attempts = env_u64("TTV_RETRY_MAX_ATTEMPTS")
+            .map(|value| value.clamp(1, u32::MAX as u64) as u32)
+            .unwrap_or(defaults.max_attempts);
+        let base_delay = env_u64("TTV_RETRY_BASE_DELAY_MS")
+            .map(Duration::from_millis)
+            .unwrap_or(defaults.base_delay);
+        let max_delay = env_u64("TTV_RETRY_MAX_DELAY_MS")
+            .map(Duration::from_millis)
+            .unwrap_or(defaults.max_delay);
+
+        Self {
+            max_attempts,
+            base_delay,
+            max_delay: max_delay.max(base_delay),
+        }
+    }
+
+    /// Delay to wait after the given (1-based) failed attempt.
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+
+    pub fn can_retry(&self, attempt: u32) -> bool {
+        attempt < self.max_attempts
+    }
+}
+
+pub fn config() -> &'static RetryConfig {
+    static CONFIG: OnceLock<RetryConfig> = OnceLock::new();
+    CONFIG.get_or_init(RetryConfig::from_env)
+}
+
+pub fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+pub fn is_retryable_error(err: &reqwest::Error) -> bool {
+    err.is_connect() || err.is_timeout()
+}
+
+fn env_u64(name: &str) -> Option<u64> {
+    env::var(name)
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+}
@@ -0,0 +1,73 @@
+use std::io;
+
+use anyhow::Result;
+use clap::Args;
+
+use crate::list::{self, Columns, Row};
+use crate::output::{self, OutputFormat};
+use crate::twitch::TwitchClient;
+use crate::{auth, config};
+
+#[derive(Debug, Args)]
+#[command(about = "Search Twitch channels by name")]
+pub struct SearchArgs {
+    #[arg(value_name = "QUERY", help = "Channel name or part of it")]
+    pub query: String,
+    #[arg(
+        long,
+        default_value_t = 20,
+        value_parser = clap::value_parser!(u8).range(1..=100),
+        help = "Maximum number of results (1-100)"
+    )]
+    pub limit: u8,
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table, help = "Output format")]
+    pub format: OutputFormat,
+}
+
+pub async fn run(args: SearchArgs) -> Result<()> {
+    let mut config = config::load_config()?;
+    if config::token_needs_refresh(&config) {
+        auth::run(auth::AuthArgs::default()).await?;
+        config = config::load_config()?;
+    }
+
+    let client = TwitchClient::from_config(&config)?;
+
+    let channels = client.search_channels(&args.query, args.limit).await?;
+    if channels.is_empty() {
+        let message = format!("No channels found for `{}`.", args.query);
+        output::write_empty(&mut io::stdout(), args.format, &message)?;
+        return Ok(());
+    }
+
+    let rows: Vec<Row> = channels
+        .into_iter()
+        .map(|channel| Row {
+            login: channel.broadcaster_login,
+            display_name: channel.display_name,
+            game_name: if channel.is_live {
+                channel.game_name
+            } else {
+                String::new()
+            },
+            status: Some(if channel.is_live { "online" } else { "offline" }),
+            viewers: None,
+            uptime_secs: None,
+            last_online_at: None,
+            followed_at: None,
+        })
+        .collect();
+
+    let columns = Columns {
+        status: true,
+        ..Columns::default()
+    };
+    list::print_rows(
+        &mut io::stdout(),
+        args.format,
+        &rows,
+        columns,
+        config.list.offline_placeholder.as_deref(),
+    )?;
+    Ok(())
+}
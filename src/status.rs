@@ -0,0 +1,134 @@
+use anyhow::{Result, bail};
+use clap::Args;
+use colored::Colorize;
+
+use crate::{config, db, paths, streamlink};
+
+#[derive(Debug, Args)]
+#[command(about = "Check that ttv is set up correctly")]
+pub struct StatusArgs {}
+
+enum Outcome {
+    Pass,
+    // Not fatal: commands recover on their own, e.g. by fetching a new token.
+    Warn,
+    Fail,
+}
+
+pub async fn run(_args: StatusArgs) -> Result<()> {
+    let mut failures = 0;
+    let mut report = |name: &str, outcome: Outcome, detail: String| {
+        let mark = match outcome {
+            Outcome::Pass => "[ok]  ".green(),
+            Outcome::Warn => "[warn]".yellow(),
+            Outcome::Fail => {
+                failures += 1;
+                "[fail]".red()
+            }
+        };
+        println!("{} {}: {}", mark, name, detail);
+    };
+
+    let path = config::config_path()?;
+    let config = if path.exists() {
+        match config::load_config() {
+            Ok(config) => {
+                report("Config", Outcome::Pass, path.display().to_string());
+                Some(config)
+            }
+            Err(err) => {
+                report("Config", Outcome::Fail, format!("{err:#}"));
+                None
+            }
+        }
+    } else {
+        report(
+            "Config",
+            Outcome::Fail,
+            format!("{} does not exist. Run `ttv config`.", path.display()),
+        );
+        Some(config::Config::default())
+    };
+
+    if let Some(config) = &config {
+        let has_id = config::require_client_id(config).is_ok();
+        let has_secret = config
+            .twitch
+            .client_secret
+            .as_deref()
+            .is_some_and(|value| !value.trim().is_empty());
+        if has_id && has_secret {
+            report(
+                "Credentials",
+                Outcome::Pass,
+                "client ID and secret are set".to_string(),
+            );
+        } else {
+            report(
+                "Credentials",
+                Outcome::Fail,
+                "Run `ttv config --client-id <ID> --client-secret <SECRET>`.".to_string(),
+            );
+        }
+
+        if !config::token_needs_refresh(config) {
+            let expires_at = config
+                .twitch
+                .expires_at
+                .map(|expires_at| expires_at.to_rfc3339())
+                .unwrap_or_default();
+            report(
+                "Access token",
+                Outcome::Pass,
+                format!("valid until {expires_at}"),
+            );
+        } else if has_id && has_secret {
+            report(
+                "Access token",
+                Outcome::Warn,
+                "missing or expired; it is fetched automatically when needed".to_string(),
+            );
+        } else {
+            report(
+                "Access token",
+                Outcome::Fail,
+                "missing or expired. Run `ttv auth`.".to_string(),
+            );
+        }
+    }
+
+    match db::connect().await {
+        Ok(pool) => {
+            pool.close().await;
+            let path = db::db_path()?;
+            report("Database", Outcome::Pass, path.display().to_string());
+        }
+        Err(err) => report("Database", Outcome::Fail, format!("{err:#}")),
+    }
+
+    match paths::cache_dir() {
+        Ok(dir) if dir.exists() => report("Cache", Outcome::Pass, dir.display().to_string()),
+        Ok(dir) => report(
+            "Cache",
+            Outcome::Pass,
+            format!("{} (created when needed)", dir.display()),
+        ),
+        Err(err) => report("Cache", Outcome::Fail, format!("{err:#}")),
+    }
+
+    let options = streamlink::LaunchOptions {
+        player: config.and_then(|config| config.player.player),
+        ..Default::default()
+    };
+    for program in ["streamlink", options.player()] {
+        match streamlink::ensure_command_available(program) {
+            Ok(()) => report(program, Outcome::Pass, "found on PATH".to_string()),
+            Err(err) => report(program, Outcome::Fail, err.to_string()),
+        }
+    }
+
+    if failures > 0 {
+        bail!("{} check(s) failed.", failures);
+    }
+    Ok(())
+}
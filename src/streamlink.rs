@@ -1,26 +1,134 @@
-use std::process::{Command as StdCommand, Stdio};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command as StdCommand, ExitStatus, Stdio};
+use std::time::Duration;
 
 use anyhow::{Context, Result, bail};
-use tokio::process::Command;
+use tokio::process::{Child, Command};
 
-const STREAMLINK_ARGS: [&str; 3] = ["--player", "mpv", "-a"];
+const DEFAULT_PLAYER: &str = "mpv";
+const DEFAULT_QUALITY: &str = "best";
+pub const AUDIO_ONLY_QUALITY: &str = "audio_only";
 const STREAMLINK_PLAYER_ARGS: &str = "--cache=yes --cache-secs=600";
+const LOW_LATENCY_PLAYER_ARGS: &str = "--cache=yes --cache-secs=5";
+// Window titles (`LaunchOptions::title`), rendered with `template::render`.
+pub const STREAM_TITLE_TEMPLATE: &str = "{login}";
+pub const VOD_TITLE_TEMPLATE: &str = "VOD {id}";
+pub const VIDEO_TITLE_TEMPLATE: &str = "{display_name}: {title}";
+// How long a stopped streamlink and its player get to exit before they are
+// killed.
+#[cfg(unix)]
+const STOP_GRACE_PERIOD: Duration = Duration::from_secs(5);
 
-pub fn ensure_dependencies() -> Result<()> {
+/// Value parser for quality arguments. Accepts one quality or a
+/// comma-separated fallback list such as `1080p60,720p,best`, which
+/// streamlink tries in order. Spaces around entries are dropped.
+pub fn parse_quality(value: &str) -> Result<String, String> {
+    let entries: Vec<&str> = value.split(',').map(str::trim).collect();
+    if entries.iter().any(|entry| entry.is_empty()) {
+        return Err(
+            "expected a quality or a comma-separated list like 1080p60,720p,best without empty entries"
+                .to_string(),
+        );
+    }
+    if let Some(entry) = entries
+        .iter()
+        .find(|entry| entry.contains(char::is_whitespace))
+    {
+        return Err(format!("quality `{entry}` must not contain spaces"));
+    }
+    Ok(entries.join(","))
+}
+
+pub fn ensure_dependencies(options: &LaunchOptions) -> Result<()> {
     ensure_command_available("streamlink")?;
-    ensure_command_available("mpv")?;
+    if !options.record_only {
+        ensure_command_available(options.player())?;
+    }
     Ok(())
 }
 
-pub async fn launch(url: &str) -> Result<()> {
-    let status = Command::new("streamlink")
-        .args(STREAMLINK_ARGS)
-        .arg(STREAMLINK_PLAYER_ARGS)
-        .arg(url)
-        .arg("best")
-        .stdin(Stdio::null())
-        .stdout(Stdio::inherit())
-        .stderr(Stdio::inherit())
+#[derive(Debug, Default, Clone)]
+pub struct LaunchOptions {
+    pub quality: Option<String>,
+    pub player: Option<String>,
+    pub audio_device: Option<String>,
+    pub video_output: Option<String>,
+    pub extra_args: Vec<String>,
+    /// Replaces the default mpv cache arguments and applies to any player.
+    pub player_args: Option<String>,
+    /// File the stream is recorded to while it plays.
+    pub record: Option<PathBuf>,
+    /// Only write `record` without starting a player.
+    pub record_only: bool,
+    /// Stops a record-only run after this long (`--hls-duration`).
+    pub segment: Option<Duration>,
+    /// Passes `--twitch-low-latency` and keeps mpv's cache short.
+    pub low_latency: bool,
+    /// Keeps mpv from opening a window for audio-only playback.
+    pub audio_only: bool,
+    /// Window title for mpv, so several streams can be told apart.
+    pub title: Option<String>,
+}
+
+impl LaunchOptions {
+    fn quality(&self) -> &str {
+        self.quality.as_deref().unwrap_or(DEFAULT_QUALITY)
+    }
+
+    pub(crate) fn player(&self) -> &str {
+        self.player.as_deref().unwrap_or(DEFAULT_PLAYER)
+    }
+
+    fn is_mpv(&self) -> bool {
+        Path::new(self.player())
+            .file_stem()
+            .is_some_and(|stem| stem == "mpv")
+    }
+
+    // The default cache and the device arguments are mpv options; other
+    // players only get configured arguments.
+    fn player_arg_string(&self) -> Option<String> {
+        if !self.is_mpv() {
+            return self
+                .player_args
+                .clone()
+                .filter(|args| !args.trim().is_empty());
+        }
+
+        let default_args = if self.low_latency {
+            LOW_LATENCY_PLAYER_ARGS
+        } else {
+            STREAMLINK_PLAYER_ARGS
+        };
+        let base = self.player_args.as_deref().unwrap_or(default_args);
+        let mut args: Vec<String> = Some(base)
+            .filter(|base| !base.trim().is_empty())
+            .map(str::to_string)
+            .into_iter()
+            .collect();
+        if let Some(device) = &self.audio_device {
+            args.push(quote_player_arg(&format!("--audio-device={device}")));
+        }
+        if let Some(vo) = &self.video_output {
+            args.push(quote_player_arg(&format!("--vo={vo}")));
+        }
+        if let Some(title) = &self.title {
+            // mpv expands `${...}` in titles; `$$` is a literal dollar sign.
+            args.push(quote_player_arg(&format!(
+                "--title={}",
+                title.replace('$', "$$")
+            )));
+        }
+        if self.audio_only {
+            args.push("--no-video".to_string());
+        }
+        (!args.is_empty()).then(|| args.join(" "))
+    }
+}
+
+pub async fn launch(url: &str, options: &LaunchOptions) -> Result<()> {
+    let status = command(url, options)
         .status()
         .await
         .context("failed to start streamlink")?;
@@ -32,21 +140,133 @@ pub async fn launch(url: &str) -> Result<()> {
     Ok(())
 }
 
-pub fn spawn(url: &str) -> Result<tokio::process::Child> {
+/// Asks streamlink which qualities `url` currently offers, ordered from worst
+/// to best as streamlink reports them.
+pub async fn qualities(url: &str) -> Result<Vec<String>> {
+    let output = Command::new("streamlink")
+        .arg(url)
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .context("failed to start streamlink")?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() {
+        let message = stdout
+            .lines()
+            .chain(String::from_utf8_lossy(&output.stderr).lines())
+            .find_map(|line| line.strip_prefix("error: "))
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("streamlink exited with status {}", output.status));
+        bail!("Could not list qualities for {}: {}", url, message);
+    }
+
+    let Some(list) = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("Available streams: "))
+    else {
+        bail!("Could not list qualities for {}: no streams reported", url);
+    };
+    Ok(parse_qualities(list))
+}
+
+// Input looks like `audio_only, 160p (worst), 720p60, 1080p60 (best)`.
+fn parse_qualities(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(|entry| entry.split_whitespace().next().unwrap_or_default())
+        .filter(|quality| !quality.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Starts streamlink without waiting for it. On Unix it gets its own process
+/// group, which the player it launches joins, so that [`stop`] reaches both.
+pub fn spawn(url: &str, options: &LaunchOptions) -> Result<Child> {
+    let mut cmd = command(url, options);
+    #[cfg(unix)]
+    cmd.process_group(0);
+    cmd.spawn()
+        .with_context(|| format!("failed to start streamlink for {}", url))
+}
+
+/// Stops a child started with [`spawn`] and waits for it. The process group is
+/// asked to exit with SIGTERM first and only killed if it is still running
+/// after a grace period.
+pub async fn stop(child: &mut Child) -> io::Result<ExitStatus> {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        signal_group(pid, libc::SIGTERM)?;
+        if let Ok(status) = tokio::time::timeout(STOP_GRACE_PERIOD, child.wait()).await {
+            return status;
+        }
+        signal_group(pid, libc::SIGKILL)?;
+        return child.wait().await;
+    }
+    child.kill().await?;
+    child.wait().await
+}
+
+#[cfg(unix)]
+fn signal_group(pgid: u32, signal: libc::c_int) -> io::Result<()> {
+    let pgid = libc::pid_t::try_from(pgid).map_err(io::Error::other)?;
+    // SAFETY: kill(2) has no memory-safety preconditions; a negative pid
+    // addresses the process group.
+    if unsafe { libc::kill(-pgid, signal) } == 0 {
+        return Ok(());
+    }
+    let err = io::Error::last_os_error();
+    // The group is already gone.
+    if err.raw_os_error() == Some(libc::ESRCH) {
+        return Ok(());
+    }
+    Err(err)
+}
+
+fn command(url: &str, options: &LaunchOptions) -> Command {
     let mut cmd = Command::new("streamlink");
-    cmd.args(STREAMLINK_ARGS)
-        .arg(STREAMLINK_PLAYER_ARGS)
+    match (&options.record, options.record_only) {
+        (Some(path), true) => {
+            cmd.arg("--output").arg(path);
+            if let Some(segment) = options.segment {
+                cmd.arg("--hls-duration").arg(segment.as_secs().to_string());
+            }
+        }
+        (record, _) => {
+            cmd.arg("--player").arg(options.player());
+            if let Some(player_args) = options.player_arg_string() {
+                cmd.arg("-a").arg(player_args);
+            }
+            if let Some(path) = record {
+                cmd.arg("--record").arg(path);
+            }
+        }
+    }
+    if options.low_latency {
+        cmd.arg("--twitch-low-latency");
+    }
+    cmd.args(&options.extra_args)
         .arg(url)
-        .arg("best")
+        .arg(options.quality())
         .stdin(Stdio::null())
         .stdout(Stdio::inherit())
         .stderr(Stdio::inherit());
+    cmd
+}
 
-    cmd.spawn()
-        .with_context(|| format!("failed to start streamlink for {}", url))
+// streamlink splits the player arguments shell-style, so values with
+// whitespace or quotes need to be wrapped.
+fn quote_player_arg(arg: &str) -> String {
+    if arg
+        .chars()
+        .any(|ch| ch.is_whitespace() || ch == '"' || ch == '\'')
+    {
+        format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        arg.to_string()
+    }
 }
 
-fn ensure_command_available(name: &str) -> Result<()> {
+pub(crate) fn ensure_command_available(name: &str) -> Result<()> {
     let result = StdCommand::new(name)
         .arg("--version")
         .stdout(Stdio::null())
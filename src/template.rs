@@ -0,0 +1,58 @@
+use anyhow::{Result, bail};
+
+/// Replaces `{name}` placeholders with the matching value. Placeholders
+/// without a value are left untouched.
+pub fn render(template: &str, values: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after.find('}') {
+            Some(end) => {
+                let name = &after[..end];
+                match values.iter().find(|(key, _)| *key == name) {
+                    Some((_, value)) => out.push_str(value),
+                    None => {
+                        out.push('{');
+                        out.push_str(name);
+                        out.push('}');
+                    }
+                }
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Fails on placeholders that are not in `allowed` or are never closed.
+pub fn validate(template: &str, allowed: &[&str]) -> Result<()> {
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('}') else {
+            bail!("unclosed placeholder in template `{}`", template);
+        };
+        let name = &after[..end];
+        if !allowed.contains(&name) {
+            bail!(
+                "unknown placeholder `{{{}}}` in template `{}`; available: {}",
+                name,
+                template,
+                allowed
+                    .iter()
+                    .map(|key| format!("{{{key}}}"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+        rest = &after[end + 1..];
+    }
+    Ok(())
+}
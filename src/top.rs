@@ -0,0 +1,87 @@
+use std::io;
+
+use anyhow::Result;
+use clap::Args;
+
+use crate::error::NotFoundError;
+use crate::list::{self, Columns, Row};
+use crate::output::{self, OutputFormat};
+use crate::twitch::TwitchClient;
+use crate::{auth, cache, config, db};
+
+#[derive(Debug, Args)]
+#[command(about = "List the most-viewed live streams on Twitch")]
+pub struct TopArgs {
+    #[arg(long, value_name = "NAME", help = "Only show streams of this game")]
+    pub game: Option<String>,
+    #[arg(
+        long,
+        default_value_t = 20,
+        value_parser = clap::value_parser!(u8).range(1..=100),
+        help = "Maximum number of streams (1-100)"
+    )]
+    pub limit: u8,
+    #[arg(
+        long,
+        requires = "game",
+        help = "Look the game up on Twitch again instead of using the cached ID"
+    )]
+    pub refresh: bool,
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table, help = "Output format")]
+    pub format: OutputFormat,
+}
+
+pub async fn run(args: TopArgs) -> Result<()> {
+    let mut config = config::load_config()?;
+    if config::token_needs_refresh(&config) {
+        auth::run(auth::AuthArgs::default()).await?;
+        config = config::load_config()?;
+    }
+
+    let client = TwitchClient::from_config(&config)?;
+
+    let game_id = match args.game.as_deref() {
+        Some(name) => {
+            let pool = db::connect().await?;
+            match cache::game_by_name(&pool, &client, name, args.refresh).await? {
+                Some(game) => Some(game.id),
+                None => {
+                    return Err(NotFoundError::error(format!(
+                        "Game `{}` not found on Twitch.",
+                        name
+                    )));
+                }
+            }
+        }
+        None => None,
+    };
+
+    let streams = client
+        .fetch_top_streams(game_id.as_deref(), args.limit)
+        .await?;
+    if streams.is_empty() {
+        output::write_empty(&mut io::stdout(), args.format, "No live streams found.")?;
+        return Ok(());
+    }
+
+    let rows: Vec<Row> = streams
+        .into_iter()
+        .map(|stream| Row {
+            login: stream.user_login,
+            display_name: stream.user_name,
+            game_name: stream.game_name,
+            status: None,
+            viewers: Some(stream.viewer_count),
+            uptime_secs: None,
+            last_online_at: None,
+            followed_at: None,
+        })
+        .collect();
+
+    let columns = Columns {
+        viewers: true,
+        ..Columns::default()
+    };
+    list::print_rows(&mut io::stdout(), args.format, &rows, columns, None)?;
+    Ok(())
+}
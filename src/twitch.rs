@@ -1,10 +1,25 @@
 use anyhow::{Context, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use futures_util::{StreamExt, TryStreamExt, stream};
+use log::debug;
 use reqwest::StatusCode;
+use reqwest::header::HeaderMap;
 use serde::Deserialize;
 use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::env;
+use std::sync::OnceLock;
 use std::time::Duration;
 
-const TWITCH_API_ENDPOINT: &str = "https://api.twitch.tv/helix";
+use crate::config;
+use crate::error::TwitchError;
+use crate::retry::{self, RetryConfig};
+
+const DEFAULT_API_BASE: &str = "https://api.twitch.tv/helix";
+const MAX_CONCURRENT_BATCHES: usize = 5;
+const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 5;
+
+static REQUEST_TIMEOUT: OnceLock<Duration> = OnceLock::new();
 
 #[derive(Debug, Deserialize)]
 pub struct TwitchUser {
@@ -21,11 +36,24 @@ struct UsersResponse {
 #[derive(Debug, Deserialize)]
 pub struct TwitchStream {
     pub user_id: String,
-    #[allow(dead_code)]
     pub user_login: String,
-    #[allow(dead_code)]
     pub user_name: String,
+    pub game_id: String,
     pub game_name: String,
+    pub title: String,
+    pub viewer_count: u64,
+    pub started_at: DateTime<Utc>,
+    /// Contains `{width}` and `{height}` placeholders; see `thumbnail`.
+    #[serde(default)]
+    pub thumbnail_url: String,
+}
+
+impl TwitchStream {
+    pub fn thumbnail(&self, width: u32, height: u32) -> String {
+        self.thumbnail_url
+            .replace("{width}", &width.to_string())
+            .replace("{height}", &height.to_string())
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -33,12 +61,59 @@ struct StreamsResponse {
     data: Vec<TwitchStream>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct TwitchGame {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GamesResponse {
+    data: Vec<TwitchGame>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TwitchChannel {
+    #[allow(dead_code)]
+    pub id: String,
+    pub broadcaster_login: String,
+    pub display_name: String,
+    pub game_name: String,
+    pub is_live: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChannelsResponse {
+    data: Vec<TwitchChannel>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TwitchFollowedChannel {
+    pub broadcaster_id: String,
+    pub broadcaster_login: String,
+    pub broadcaster_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct FollowedChannelsResponse {
+    data: Vec<TwitchFollowedChannel>,
+    #[serde(default)]
+    pagination: Pagination,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Pagination {
+    cursor: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct TwitchVod {
     pub id: String,
     pub title: String,
     pub duration: String,
     pub created_at: String,
+    #[serde(rename = "type")]
+    pub video_type: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -46,95 +121,314 @@ struct VodsResponse {
     data: Vec<TwitchVod>,
 }
 
-pub async fn fetch_users_by_login(
-    client_id: &str,
-    access_token: &str,
-    logins: &[String],
-) -> Result<Vec<TwitchUser>> {
-    if logins.is_empty() {
-        return Ok(Vec::new());
-    }
+#[derive(Debug, Deserialize)]
+pub struct TwitchClip {
+    #[allow(dead_code)]
+    pub id: String,
+    pub url: String,
+    pub title: String,
+    pub view_count: u64,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClipsResponse {
+    data: Vec<TwitchClip>,
+}
+
+/// Overrides `twitch.request_timeout_secs` for this run. Must be called
+/// before the first request.
+pub fn set_request_timeout(secs: u64) {
+    let _ = REQUEST_TIMEOUT.set(Duration::from_secs(secs));
+}
 
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(5))
-        .build()
-        .context("failed to build Twitch API client")?;
+fn request_timeout(config: &config::Config) -> Duration {
+    REQUEST_TIMEOUT.get().copied().unwrap_or_else(|| {
+        Duration::from_secs(
+            config
+                .twitch
+                .request_timeout_secs
+                .unwrap_or(DEFAULT_REQUEST_TIMEOUT_SECS),
+        )
+    })
+}
 
-    let mut users = Vec::new();
-    for batch in logins.chunks(100) {
-        let url = build_users_url(batch)?;
-        let response: UsersResponse = get_twitch(&client, client_id, access_token, url).await?;
-        users.extend(response.data);
+// One client per run, so concurrent batches share its connection pool. It is
+// also used for the auth requests and built from the config passed on the
+// first call. Without `twitch.proxy`, reqwest picks up
+// HTTPS_PROXY/HTTP_PROXY/NO_PROXY from the environment.
+pub(crate) fn client(config: &config::Config) -> Result<&'static reqwest::Client> {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    if let Some(client) = CLIENT.get() {
+        return Ok(client);
     }
+    let client = build_client(config)?;
+    Ok(CLIENT.get_or_init(|| client))
+}
 
-    Ok(users)
+fn build_client(config: &config::Config) -> Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder().timeout(request_timeout(config));
+    if let Some(proxy) = configured_proxy(config) {
+        let proxy = reqwest::Proxy::all(proxy)
+            .with_context(|| format!("invalid twitch.proxy URL `{proxy}`"))?;
+        builder = builder.proxy(proxy);
+    }
+    builder.build().context("failed to build Twitch API client")
 }
 
-pub async fn fetch_streams_by_user_ids(
-    client_id: &str,
-    access_token: &str,
-    ids: &[String],
-) -> Result<Vec<TwitchStream>> {
-    if ids.is_empty() {
-        return Ok(Vec::new());
+fn configured_proxy(config: &config::Config) -> Option<&str> {
+    config
+        .twitch
+        .proxy
+        .as_deref()
+        .map(str::trim)
+        .filter(|proxy| !proxy.is_empty())
+}
+
+/// Helix API client for one set of credentials. The underlying HTTP client
+/// is shared by every `TwitchClient` in the process.
+pub struct TwitchClient {
+    http: &'static reqwest::Client,
+    api_base: String,
+    client_id: String,
+    access_token: String,
+}
+
+impl TwitchClient {
+    fn new(config: &config::Config, client_id: &str, access_token: &str) -> Result<Self> {
+        Ok(Self {
+            http: client(config)?,
+            api_base: api_base(),
+            client_id: client_id.to_string(),
+            access_token: access_token.to_string(),
+        })
     }
 
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(5))
-        .build()
-        .context("failed to build Twitch API client")?;
+    /// Uses the client ID and app access token from the config.
+    pub fn from_config(config: &config::Config) -> Result<Self> {
+        let client_id = config::require_client_id(config)?;
+        let access_token = config::require_access_token(config)?;
+        Self::new(config, client_id, access_token)
+    }
 
-    let mut streams = Vec::new();
-    for batch in ids.chunks(100) {
-        let url = build_streams_url(batch)?;
-        let response: StreamsResponse = get_twitch(&client, client_id, access_token, url).await?;
-        streams.extend(response.data);
+    /// Uses the client ID and the user access token from `auth --user`.
+    pub fn from_user_config(config: &config::Config) -> Result<Self> {
+        let client_id = config::require_client_id(config)?;
+        let access_token = config::require_user_access_token(config)?;
+        Self::new(config, client_id, access_token)
     }
 
-    Ok(streams)
-}
+    pub async fn fetch_users_by_login(&self, logins: &[String]) -> Result<Vec<TwitchUser>> {
+        self.get_batches(
+            logins,
+            |batch| build_users_url(&self.api_base, "login", batch),
+            |response: UsersResponse| response.data,
+        )
+        .await
+    }
 
-pub async fn fetch_user_by_login(
-    client_id: &str,
-    access_token: &str,
-    login: &str,
-) -> Result<TwitchUser> {
-    let users = fetch_users_by_login(client_id, access_token, &[login.to_string()]).await?;
-    users
-        .into_iter()
-        .next()
-        .ok_or_else(|| anyhow::anyhow!("No streamer found with login `{}`.", login))
-}
+    pub async fn fetch_users_by_id(&self, ids: &[String]) -> Result<Vec<TwitchUser>> {
+        self.get_batches(
+            ids,
+            |batch| build_users_url(&self.api_base, "id", batch),
+            |response: UsersResponse| response.data,
+        )
+        .await
+    }
 
-pub async fn fetch_vods_by_user_id(
-    client_id: &str,
-    access_token: &str,
-    user_id: &str,
-) -> Result<Vec<TwitchVod>> {
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(5))
-        .build()
-        .context("failed to build Twitch API client")?;
+    pub async fn fetch_streams_by_user_ids(&self, ids: &[String]) -> Result<Vec<TwitchStream>> {
+        let mut streams = self
+            .get_batches(
+                ids,
+                |batch| build_streams_url(&self.api_base, batch),
+                |response: StreamsResponse| response.data,
+            )
+            .await?;
+
+        self.fill_game_names(&mut streams).await?;
+        Ok(streams)
+    }
+
+    /// Most-viewed live streams, optionally limited to one game. Helix orders
+    /// `/streams` by viewer count when no user filter is given.
+    pub async fn fetch_top_streams(
+        &self,
+        game_id: Option<&str>,
+        first: u8,
+    ) -> Result<Vec<TwitchStream>> {
+        let url = build_top_streams_url(&self.api_base, game_id, first)?;
+        let response: StreamsResponse = self.get(url).await?;
+        let mut streams = response.data;
+
+        self.fill_game_names(&mut streams).await?;
+        Ok(streams)
+    }
+
+    /// Categories with the most viewers right now, most viewed first.
+    pub async fn fetch_top_games(&self, first: u8) -> Result<Vec<TwitchGame>> {
+        let url = build_top_games_url(&self.api_base, first)?;
+        let response: GamesResponse = self.get(url).await?;
+        Ok(response.data)
+    }
+
+    pub async fn fetch_game_by_name(&self, name: &str) -> Result<Option<TwitchGame>> {
+        let url = build_game_by_name_url(&self.api_base, name)?;
+        let response: GamesResponse = self.get(url).await?;
+        Ok(response.data.into_iter().next())
+    }
+
+    /// `video_type` is `archive`, `highlight`, or `upload`; `None` asks for all
+    /// types. Returns at most `first` (up to 100) of the most recent videos.
+    pub async fn fetch_vods_by_user_id(
+        &self,
+        user_id: &str,
+        video_type: Option<&str>,
+        first: u8,
+    ) -> Result<Vec<TwitchVod>> {
+        let url = build_vods_url(&self.api_base, user_id, video_type, first)?;
+        let response: VodsResponse = self.get(url).await?;
+        Ok(response.data)
+    }
+
+    pub async fn fetch_clips_by_user_id(
+        &self,
+        user_id: &str,
+        first: u8,
+    ) -> Result<Vec<TwitchClip>> {
+        let url = build_clips_url(&self.api_base, user_id, first)?;
+        let response: ClipsResponse = self.get(url).await?;
+        Ok(response.data)
+    }
+
+    /// Every channel `user_id` follows, fetched 100 per page. Needs a user
+    /// token for that user with the `user:read:follows` scope.
+    pub async fn fetch_followed_channels(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<TwitchFollowedChannel>> {
+        let mut channels = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let url = build_followed_channels_url(&self.api_base, user_id, cursor.as_deref())?;
+            let response: FollowedChannelsResponse = self.get(url).await?;
+            let page_empty = response.data.is_empty();
+            channels.extend(response.data);
+            // The last page comes without a cursor. An empty page also ends the
+            // loop so that a cursor that never runs out cannot loop forever.
+            match response
+                .pagination
+                .cursor
+                .filter(|cursor| !cursor.is_empty())
+            {
+                Some(next) if !page_empty => cursor = Some(next),
+                _ => return Ok(channels),
+            }
+        }
+    }
+
+    pub async fn search_channels(&self, query: &str, limit: u8) -> Result<Vec<TwitchChannel>> {
+        let url = build_search_channels_url(&self.api_base, query, limit)?;
+        let response: ChannelsResponse = self.get(url).await?;
+        Ok(response.data)
+    }
+
+    // `/streams` sometimes returns an empty `game_name` while `game_id` is set;
+    // look those names up with one `/games` request per 100 ids.
+    async fn fill_game_names(&self, streams: &mut [TwitchStream]) -> Result<()> {
+        let mut ids: Vec<String> = streams
+            .iter()
+            .filter(|stream| stream.game_name.is_empty() && !stream.game_id.is_empty())
+            .map(|stream| stream.game_id.clone())
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+
+        let games = self
+            .get_batches(
+                &ids,
+                |batch| build_games_url(&self.api_base, batch),
+                |response: GamesResponse| response.data,
+            )
+            .await?;
+        let names: HashMap<String, String> =
+            games.into_iter().map(|game| (game.id, game.name)).collect();
+
+        for stream in streams
+            .iter_mut()
+            .filter(|stream| stream.game_name.is_empty())
+        {
+            if let Some(name) = names.get(&stream.game_id) {
+                stream.game_name = name.clone();
+            }
+        }
+        Ok(())
+    }
 
-    let url = build_vods_url(user_id)?;
-    let response: VodsResponse = get_twitch(&client, client_id, access_token, url).await?;
-    Ok(response.data)
+    // Helix accepts up to 100 values per request. Batches run concurrently, at
+    // most MAX_CONCURRENT_BATCHES at a time; results keep the input order and
+    // any failed batch fails the whole call. No values means no request.
+    async fn get_batches<R, T>(
+        &self,
+        values: &[String],
+        build_url: impl Fn(&[String]) -> Result<reqwest::Url>,
+        into_data: fn(R) -> Vec<T>,
+    ) -> Result<Vec<T>>
+    where
+        R: DeserializeOwned,
+    {
+        let urls = values
+            .chunks(100)
+            .map(build_url)
+            .collect::<Result<Vec<_>>>()?;
+        let responses: Vec<R> = stream::iter(urls)
+            .map(|url| self.get(url))
+            .buffered(MAX_CONCURRENT_BATCHES)
+            .try_collect()
+            .await?;
+        Ok(responses.into_iter().flat_map(into_data).collect())
+    }
+
+    async fn get<T>(&self, url: reqwest::Url) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        get_twitch(
+            self.http,
+            retry::config(),
+            &self.client_id,
+            &self.access_token,
+            url,
+        )
+        .await
+    }
 }
 
-fn build_users_url(logins: &[String]) -> Result<reqwest::Url> {
-    let mut url = reqwest::Url::parse(&format!("{}/users", TWITCH_API_ENDPOINT))
+fn build_users_url(base: &str, key: &str, values: &[String]) -> Result<reqwest::Url> {
+    let mut url = reqwest::Url::parse(&format!("{}/users", base))
         .context("failed to build Twitch users URL")?;
     {
         let mut pairs = url.query_pairs_mut();
-        for login in logins {
-            pairs.append_pair("login", login);
+        for value in values {
+            pairs.append_pair(key, value);
         }
     }
     Ok(url)
 }
 
-fn build_streams_url(ids: &[String]) -> Result<reqwest::Url> {
-    let mut url = reqwest::Url::parse(&format!("{}/streams", TWITCH_API_ENDPOINT))
+fn build_games_url(base: &str, ids: &[String]) -> Result<reqwest::Url> {
+    let mut url = reqwest::Url::parse(&format!("{}/games", base))
+        .context("failed to build Twitch games URL")?;
+    {
+        let mut pairs = url.query_pairs_mut();
+        for id in ids {
+            pairs.append_pair("id", id);
+        }
+    }
+    Ok(url)
+}
+
+fn build_streams_url(base: &str, ids: &[String]) -> Result<reqwest::Url> {
+    let mut url = reqwest::Url::parse(&format!("{}/streams", base))
         .context("failed to build Twitch streams URL")?;
     {
         let mut pairs = url.query_pairs_mut();
@@ -145,19 +439,106 @@ fn build_streams_url(ids: &[String]) -> Result<reqwest::Url> {
     Ok(url)
 }
 
-fn build_vods_url(user_id: &str) -> Result<reqwest::Url> {
-    let mut url = reqwest::Url::parse(&format!("{}/videos", TWITCH_API_ENDPOINT))
+fn build_top_streams_url(base: &str, game_id: Option<&str>, first: u8) -> Result<reqwest::Url> {
+    let mut url = reqwest::Url::parse(&format!("{}/streams", base))
+        .context("failed to build Twitch streams URL")?;
+    {
+        let mut pairs = url.query_pairs_mut();
+        if let Some(game_id) = game_id {
+            pairs.append_pair("game_id", game_id);
+        }
+        pairs.append_pair("first", &first.to_string());
+    }
+    Ok(url)
+}
+
+fn build_top_games_url(base: &str, first: u8) -> Result<reqwest::Url> {
+    let mut url = reqwest::Url::parse(&format!("{}/games/top", base))
+        .context("failed to build Twitch top games URL")?;
+    url.query_pairs_mut()
+        .append_pair("first", &first.to_string());
+    Ok(url)
+}
+
+fn build_game_by_name_url(base: &str, name: &str) -> Result<reqwest::Url> {
+    let mut url = reqwest::Url::parse(&format!("{}/games", base))
+        .context("failed to build Twitch games URL")?;
+    url.query_pairs_mut().append_pair("name", name);
+    Ok(url)
+}
+
+fn build_vods_url(
+    base: &str,
+    user_id: &str,
+    video_type: Option<&str>,
+    first: u8,
+) -> Result<reqwest::Url> {
+    let mut url = reqwest::Url::parse(&format!("{}/videos", base))
         .context("failed to build Twitch videos URL")?;
     {
         let mut pairs = url.query_pairs_mut();
         pairs.append_pair("user_id", user_id);
-        pairs.append_pair("type", "archive");
+        if let Some(video_type) = video_type {
+            pairs.append_pair("type", video_type);
+        }
+        pairs.append_pair("first", &first.to_string());
+    }
+    Ok(url)
+}
+
+fn build_clips_url(base: &str, user_id: &str, first: u8) -> Result<reqwest::Url> {
+    let mut url = reqwest::Url::parse(&format!("{}/clips", base))
+        .context("failed to build Twitch clips URL")?;
+    {
+        let mut pairs = url.query_pairs_mut();
+        pairs.append_pair("broadcaster_id", user_id);
+        pairs.append_pair("first", &first.to_string());
+    }
+    Ok(url)
+}
+
+fn build_followed_channels_url(
+    base: &str,
+    user_id: &str,
+    after: Option<&str>,
+) -> Result<reqwest::Url> {
+    let mut url = reqwest::Url::parse(&format!("{}/channels/followed", base))
+        .context("failed to build Twitch followed channels URL")?;
+    {
+        let mut pairs = url.query_pairs_mut();
+        pairs.append_pair("user_id", user_id);
+        pairs.append_pair("first", "100");
+        if let Some(after) = after {
+            pairs.append_pair("after", after);
+        }
+    }
+    Ok(url)
+}
+
+fn build_search_channels_url(base: &str, query: &str, limit: u8) -> Result<reqwest::Url> {
+    let mut url = reqwest::Url::parse(&format!("{}/search/channels", base))
+        .context("failed to build Twitch search URL")?;
+    {
+        let mut pairs = url.query_pairs_mut();
+        pairs.append_pair("query", query);
+        pairs.append_pair("first", &limit.to_string());
     }
     Ok(url)
 }
 
+// `TTV_TWITCH_API_BASE` points the client at another Helix-compatible
+// server, e.g. a local mock.
+fn api_base() -> String {
+    env::var("TTV_TWITCH_API_BASE")
+        .ok()
+        .map(|value| value.trim().trim_end_matches('/').to_string())
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| DEFAULT_API_BASE.to_string())
+}
+
 async fn get_twitch<T>(
     client: &reqwest::Client,
+    retry: &RetryConfig,
     client_id: &str,
     access_token: &str,
     url: reqwest::Url,
@@ -165,38 +546,163 @@ async fn get_twitch<T>(
 where
     T: DeserializeOwned,
 {
-    let res = client
-        .get(url)
-        .header(
-            reqwest::header::AUTHORIZATION,
-            format!("Bearer {}", access_token),
-        )
-        .header("Client-ID", client_id)
-        .send()
-        .await
-        .context("failed to send Twitch request")?;
+    let mut attempt = 1;
+    loop {
+        let result = client
+            .get(url.clone())
+            .header(
+                reqwest::header::AUTHORIZATION,
+                format!("Bearer {}", access_token),
+            )
+            .header("Client-ID", client_id)
+            .send()
+            .await;
+
+        let res = match result {
+            Ok(res) => res,
+            Err(err) if retry::is_retryable_error(&err) && retry.can_retry(attempt) => {
+                tokio::time::sleep(retry.delay_for(attempt)).await;
+                attempt += 1;
+                continue;
+            }
+            Err(err) => return Err(err).context("failed to send Twitch request"),
+        };
+
+        let status = res.status();
+        debug!(
+            "GET {} -> {}{}",
+            url.path(),
+            status,
+            rate_limit_note(res.headers())
+        );
+        if retry::is_retryable_status(status) && retry.can_retry(attempt) {
+            tokio::time::sleep(retry.delay_for(attempt)).await;
+            attempt += 1;
+            continue;
+        }
+
+        let body = res.text().await.unwrap_or_default();
+        if !status.is_success() {
+            return Err(map_api_error(status, body));
+        }
 
-    let status = res.status();
-    let body = res.text().await.unwrap_or_default();
-    if !status.is_success() {
-        return Err(map_api_error(status, body));
+        let parsed = serde_json::from_str(&body).context("failed to parse Twitch response")?;
+        return Ok(parsed);
     }
+}
 
-    let parsed = serde_json::from_str(&body).context("failed to parse Twitch response")?;
-    Ok(parsed)
+// Twitch reports the points left in the token's bucket and when the bucket
+// refills (a Unix timestamp) on every response.
+fn rate_limit_note(headers: &HeaderMap) -> String {
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+    let Some(remaining) = header("ratelimit-remaining") else {
+        return String::new();
+    };
+    let mut note = match header("ratelimit-limit") {
+        Some(limit) => format!(" (rate limit: {remaining}/{limit} points left"),
+        None => format!(" (rate limit: {remaining} points left"),
+    };
+    if let Some(reset) = header("ratelimit-reset")
+        .and_then(|value| value.parse::<i64>().ok())
+        .and_then(|secs| DateTime::from_timestamp(secs, 0))
+    {
+        note.push_str(&format!(
+            ", resets at {}",
+            reset.to_rfc3339_opts(SecondsFormat::Secs, true)
+        ));
+    }
+    note.push(')');
+    note
 }
 
 fn map_api_error(status: StatusCode, body: String) -> anyhow::Error {
     match status {
-        StatusCode::UNAUTHORIZED => anyhow::anyhow!(
-            "Unauthorized Twitch API request. Run `ttv auth` to refresh your token."
+        StatusCode::UNAUTHORIZED => TwitchError::error(
+            status,
+            "Unauthorized Twitch API request. Run `ttv auth` to refresh your token.",
+        ),
+        StatusCode::FORBIDDEN => TwitchError::error(
+            status,
+            "Forbidden Twitch API request. Check your client ID and token.",
         ),
-        StatusCode::FORBIDDEN => {
-            anyhow::anyhow!("Forbidden Twitch API request. Check your client ID and token.")
-        }
         StatusCode::TOO_MANY_REQUESTS => {
-            anyhow::anyhow!("Twitch API rate limit exceeded. Try again later.")
+            TwitchError::error(status, "Twitch API rate limit exceeded. Try again later.")
+        }
+        _ => TwitchError::error(
+            status,
+            format!("Unexpected Twitch API response ({}). {}", status, body),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn mock_client(server: &MockServer) -> TwitchClient {
+        TwitchClient {
+            http: client(&config::Config::default()).unwrap(),
+            api_base: server.uri(),
+            client_id: "client-id".to_string(),
+            access_token: "token".to_string(),
         }
-        _ => anyhow::anyhow!("Unexpected Twitch API response ({}). {}", status, body),
+    }
+
+    #[test]
+    fn build_client_uses_the_configured_proxy() {
+        let mut config = config::Config::default();
+        config.twitch.proxy = Some("  ".to_string());
+        assert_eq!(configured_proxy(&config), None);
+
+        config.twitch.proxy = Some("socks5://127.0.0.1:1080".to_string());
+        assert_eq!(configured_proxy(&config), Some("socks5://127.0.0.1:1080"));
+        assert!(build_client(&config).is_ok());
+
+        config.twitch.proxy = Some("not a proxy".to_string());
+        let err = build_client(&config).unwrap_err();
+        assert!(err.to_string().contains("invalid twitch.proxy URL"));
+    }
+
+    #[tokio::test]
+    async fn fetch_users_by_login_sends_credentials_and_parses_users() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/users"))
+            .and(query_param("login", "jonhoo"))
+            .and(header("Client-ID", "client-id"))
+            .and(header("Authorization", "Bearer token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": [{"id": "1", "login": "jonhoo", "display_name": "Jonhoo"}]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let users = mock_client(&server)
+            .fetch_users_by_login(&["jonhoo".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(users.len(), 1);
+        assert_eq!(users[0].id, "1");
+        assert_eq!(users[0].display_name, "Jonhoo");
+    }
+
+    #[tokio::test]
+    async fn fetch_users_by_login_reports_rejected_tokens() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/users"))
+            .respond_with(ResponseTemplate::new(401))
+            .mount(&server)
+            .await;
+
+        let err = mock_client(&server)
+            .fetch_users_by_login(&["jonhoo".to_string()])
+            .await
+            .unwrap_err();
+        let status = err.downcast_ref::<TwitchError>().map(|err| err.status);
+        assert_eq!(status, Some(reqwest::StatusCode::UNAUTHORIZED));
     }
 }
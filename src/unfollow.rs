@@ -1,49 +1,174 @@
-use std::collections::HashSet;
+use std::collections::HashMap;
 
 use anyhow::Result;
 use clap::Args;
+use log::info;
+use sqlx::SqlitePool;
 
-use crate::db;
+use crate::{db, login, prompt};
 
 #[derive(Debug, Args)]
 #[command(about = "Unfollow Twitch streamers locally")]
 pub struct UnfollowArgs {
-    #[arg(value_name = "LOGIN", required = true, num_args = 1.., help = "Twitch login name(s) to unfollow")]
+    #[arg(
+        value_name = "LOGIN",
+        required_unless_present_any = ["all", "glob"],
+        num_args = 1..,
+        help = "Twitch login name(s) to unfollow"
+    )]
     pub logins: Vec<String>,
-    #[arg(long, help = "Print verbose delete details")]
-    pub verbose: bool,
+    #[arg(
+        long,
+        conflicts_with = "logins",
+        help = "Unfollow every followed streamer"
+    )]
+    pub all: bool,
+    #[arg(
+        long,
+        value_name = "PATTERN",
+        conflicts_with_all = ["logins", "all"],
+        help = "Unfollow every streamer whose login matches PATTERN (* and ? wildcards)"
+    )]
+    pub glob: Option<String>,
+    #[arg(
+        long,
+        help = "Print what would be unfollowed without removing anything"
+    )]
+    pub dry_run: bool,
 }
 
 pub async fn run(args: UnfollowArgs) -> Result<()> {
     let pool = db::connect().await?;
+    let (targets, missing) = targets(&pool, &args).await?;
 
-    let mut removed = 0u64;
-    let mut missing = Vec::new();
-    let mut seen = HashSet::new();
-    for login in &args.logins {
-        let key = login.to_lowercase();
-        if !seen.insert(key) {
-            continue;
+    if args.dry_run {
+        for login in &targets {
+            println!("Would remove {}", login);
         }
+        report_missing(&missing);
+        println!("Would unfollow {} streamer(s).", targets.len());
+        return Ok(());
+    }
 
-        if args.verbose {
-            eprintln!("[INFO] Removing {}", login);
-        }
-        let affected = db::delete_streamer_by_login(&pool, login).await?;
-        if affected == 0 {
-            missing.push(login.clone());
+    if args.all && !prompt::confirm("Unfollow all followed streamers?")? {
+        println!("Unfollowed 0 streamer(s).");
+        return Ok(());
+    }
+
+    info!("Removing {}", targets.join(", "));
+    let deleted = db::delete_streamers_by_logins(&pool, &targets).await?;
+    for login in &deleted {
+        // Glob matches are not known up front, so they are always listed.
+        if args.glob.is_some() {
+            println!("Removed {}", login);
         } else {
-            removed += affected;
-            if args.verbose {
-                eprintln!("[INFO] Removed {}", login);
-            }
+            info!("Removed {}", login);
+        }
+    }
+    report_missing(&missing);
+    println!("Unfollowed {} streamer(s).", deleted.len());
+    Ok(())
+}
+
+// Stored logins of the followed streamers selected by the arguments, and the
+// requested logins that are not followed.
+async fn targets(pool: &SqlitePool, args: &UnfollowArgs) -> Result<(Vec<String>, Vec<String>)> {
+    let streamers = db::list_streamers(pool).await?;
+    if args.all {
+        let targets = streamers
+            .into_iter()
+            .map(|streamer| streamer.name)
+            .collect();
+        return Ok((targets, Vec::new()));
+    }
+    if let Some(pattern) = &args.glob {
+        let targets = streamers
+            .into_iter()
+            .filter(|streamer| glob_match(pattern, &streamer.name))
+            .map(|streamer| streamer.name)
+            .collect();
+        return Ok((targets, Vec::new()));
+    }
+
+    let followed: HashMap<String, String> = streamers
+        .into_iter()
+        .map(|streamer| (streamer.name.to_lowercase(), streamer.name))
+        .collect();
+    let mut targets = Vec::new();
+    let mut missing = Vec::new();
+    for login in login::dedup_logins(args.logins.clone()) {
+        match followed.get(&login.to_lowercase()) {
+            Some(name) => targets.push(name.clone()),
+            None => missing.push(login),
         }
     }
+    Ok((targets, missing))
+}
 
+fn report_missing(missing: &[String]) {
     if !missing.is_empty() {
         eprintln!("Not followed: {}", missing.join(", "));
     }
+}
 
-    println!("Unfollowed {} streamer(s).", removed);
-    Ok(())
+// `*` matches any run of characters and `?` exactly one. Case is ignored,
+// like it is for logins.
+fn glob_match(pattern: &str, value: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let value: Vec<char> = value.to_lowercase().chars().collect();
+    let (mut p, mut v) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while v < value.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, v));
+                p += 1;
+            }
+            Some(&ch) if ch == '?' || ch == value[v] => {
+                p += 1;
+                v += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    v = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&ch| ch == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_match_handles_wildcards() {
+        assert!(glob_match("*", "jonhoo"));
+        assert!(glob_match("jon*", "jonhoo"));
+        assert!(glob_match("*hoo", "jonhoo"));
+        assert!(glob_match("*nho*", "jonhoo"));
+        assert!(glob_match("j*o*o", "jonhoo"));
+        assert!(glob_match("jonho?", "jonhoo"));
+        assert!(glob_match("?on*", "jonhoo"));
+        assert!(glob_match("jonhoo", "jonhoo"));
+    }
+
+    #[test]
+    fn glob_match_rejects_non_matches() {
+        assert!(!glob_match("xqc*", "jonhoo"));
+        assert!(!glob_match("*xqc", "jonhoo"));
+        assert!(!glob_match("jonho?", "jonho"));
+        assert!(!glob_match("jon", "jonhoo"));
+        assert!(!glob_match("?", ""));
+    }
+
+    #[test]
+    fn glob_match_ignores_case() {
+        assert!(glob_match("JON*", "jonhoo"));
+        assert!(glob_match("jon*", "JonHoo"));
+    }
 }
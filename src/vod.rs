@@ -1,83 +1,390 @@
-use std::io::{self, Write};
+use std::io::{self, IsTerminal, Write};
 
-use anyhow::{bail, Context, Result};
-use clap::Args;
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Days, Local, NaiveDate, Utc};
+use clap::{Args, ValueEnum};
+use colored::{ColoredString, Colorize};
+use serde::Serialize;
 
-use crate::{auth, config, streamlink, twitch};
+use crate::output::{self, Align, OutputFormat};
+use crate::twitch::{TwitchClient, TwitchVod};
+use crate::{auth, cache, config, db, prompt, streamlink, template};
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum VodType {
+    Archive,
+    Highlight,
+    Upload,
+    All,
+}
+
+impl VodType {
+    fn api_value(self) -> Option<&'static str> {
+        match self {
+            Self::Archive => Some("archive"),
+            Self::Highlight => Some("highlight"),
+            Self::Upload => Some("upload"),
+            Self::All => None,
+        }
+    }
+}
 
 #[derive(Debug, Args)]
 #[command(about = "Watch VODs for a Twitch streamer")]
 pub struct VodArgs {
     #[arg(value_name = "LOGIN", help = "Twitch login name")]
     pub login: String,
+    #[arg(
+        long = "type",
+        value_enum,
+        default_value_t = VodType::Archive,
+        help = "Kind of videos to list"
+    )]
+    pub video_type: VodType,
+    #[arg(
+        long,
+        value_parser = streamlink::parse_quality,
+        help = "VOD quality or fallback list like 1080p60,720p,best (defaults to config default_quality, then best)"
+    )]
+    pub quality: Option<String>,
+    #[arg(long, help = "Player executable (defaults to config player, then mpv)")]
+    pub player: Option<String>,
+    #[arg(
+        long,
+        value_name = "DATE",
+        help = "Only list VODs created on or after this date (YYYY-MM-DD in local time, or RFC3339)"
+    )]
+    pub since: Option<String>,
+    #[arg(
+        long,
+        value_name = "DATE",
+        help = "Only list VODs created on or before this date (YYYY-MM-DD in local time, or RFC3339)"
+    )]
+    pub until: Option<String>,
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 10,
+        value_parser = clap::value_parser!(u8).range(1..=100),
+        help = "Show at most N VODs to choose from (1-100)"
+    )]
+    pub count: u8,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = OutputFormat::Table,
+        help = "Output format; json and plain only print the VODs without asking which to play"
+    )]
+    pub format: OutputFormat,
+    #[arg(
+        long,
+        visible_alias = "no-launch",
+        conflicts_with_all = ["quality", "player", "format"],
+        help = "Print the URL of the chosen VOD instead of playing it"
+    )]
+    pub print_url: bool,
+    #[arg(
+        long,
+        conflicts_with = "format",
+        help = "Choose the VOD with the fuzzy finder even when stdin is not a terminal"
+    )]
+    pub interactive: bool,
 }
 
+// Date-filtered listings search Twitch's maximum page so that older VODs can
+// match.
+const FILTERED_VOD_COUNT: u8 = 100;
+
 pub async fn run(args: VodArgs) -> Result<()> {
-    streamlink::ensure_dependencies()?;
+    let since = args
+        .since
+        .as_deref()
+        .map(|value| parse_date(value, "--since", false))
+        .transpose()?;
+    let until = args
+        .until
+        .as_deref()
+        .map(|value| parse_date(value, "--until", true))
+        .transpose()?;
+    if let (Some(since), Some(until)) = (since, until)
+        && since > until
+    {
+        bail!("--since must not be later than --until.");
+    }
+    let filtered = since.is_some() || until.is_some();
 
     let mut config = config::load_config()?;
+    let mut options = streamlink::LaunchOptions {
+        quality: args.quality.or(config.player.default_quality.clone()),
+        player: args.player.or(config.player.player.clone()),
+        player_args: config.player.player_args.clone(),
+        extra_args: config.player.streamlink_args.clone(),
+        ..Default::default()
+    };
+    // Only the table view goes on to launch a player
+    if args.format == OutputFormat::Table && !args.print_url {
+        streamlink::ensure_dependencies(&options)?;
+    }
+
     if config::token_needs_refresh(&config) {
-        auth::run(auth::AuthArgs {
-            show: false,
-            verbose: false,
-        })
-        .await?;
+        auth::run(auth::AuthArgs::default()).await?;
         config = config::load_config()?;
     }
 
-    let client_id = config::require_client_id(&config)?;
-    let access_token = config::require_access_token(&config)?;
+    let client = TwitchClient::from_config(&config)?;
 
-    let user = twitch::fetch_user_by_login(client_id, access_token, &args.login).await?;
-    let vods = twitch::fetch_vods_by_user_id(client_id, access_token, &user.id).await?;
+    let pool = db::connect().await?;
+    let user = cache::user_by_login(&pool, &config, &client, &args.login).await?;
+    let first = if filtered {
+        FILTERED_VOD_COUNT
+    } else {
+        args.count
+    };
+    let mut vods = client
+        .fetch_vods_by_user_id(&user.id, args.video_type.api_value(), first)
+        .await?;
+    if filtered {
+        vods.retain(|vod| {
+            DateTime::parse_from_rfc3339(&vod.created_at).is_ok_and(|at| {
+                since.is_none_or(|since| at >= since) && until.is_none_or(|until| at <= until)
+            })
+        });
+    }
+    vods.truncate(args.count.into());
 
     if vods.is_empty() {
-        println!("No VODs found for {}.", user.display_name);
+        let message = if filtered {
+            format!(
+                "No VODs found for {} in the given date range.",
+                user.display_name
+            )
+        } else {
+            format!("No VODs found for {}.", user.display_name)
+        };
+        if args.print_url {
+            eprintln!("{message}");
+        } else {
+            output::write_empty(&mut io::stdout(), args.format, &message)?;
+        }
         return Ok(());
     }
 
-    println!("VODs for {}:", user.display_name);
-    for (idx, vod) in vods.iter().enumerate() {
-        println!(
-            "{:>2}) [{}] {} ({})",
-            idx + 1,
-            vod.created_at,
-            vod.title,
-            vod.duration
-        );
+    let fuzzy = args.interactive || io::stdin().is_terminal();
+    match args.format {
+        // With `--print-url` only the URL goes to stdout. The fuzzy finder
+        // lists the VODs itself; the numeric prompt needs the table.
+        OutputFormat::Table if args.print_url => {
+            if !fuzzy {
+                let mut out = io::stderr();
+                writeln!(out, "VODs for {}:", user.display_name)?;
+                print_table(&mut out, &vods)?;
+            }
+        }
+        OutputFormat::Table => {
+            let mut out = io::stdout();
+            writeln!(out, "VODs for {}:", user.display_name)?;
+            print_table(&mut out, &vods)?;
+        }
+        OutputFormat::Json => {
+            let rows: Vec<JsonVod> = vods.iter().map(JsonVod::from).collect();
+            output::write_json(&mut io::stdout(), &rows)?;
+            return Ok(());
+        }
+        OutputFormat::Plain => {
+            let rows: Vec<Vec<String>> = vods
+                .iter()
+                .map(|vod| {
+                    vec![
+                        vod.id.clone(),
+                        vod.created_at.clone(),
+                        format_duration(&vod.duration),
+                        vod.video_type.clone(),
+                        vod.title.clone(),
+                    ]
+                })
+                .collect();
+            output::write_plain(&mut io::stdout(), &rows)?;
+            return Ok(());
+        }
     }
 
-    let selection = prompt_selection(vods.len())?;
+    // On a terminal, typing filters the VODs by date, type, and title.
+    let selection = if fuzzy {
+        let labels: Vec<String> = vods
+            .iter()
+            .map(|vod| {
+                format!(
+                    "{}  {}  {}",
+                    format_created_at(&vod.created_at),
+                    vod.video_type,
+                    vod.title
+                )
+            })
+            .collect();
+        prompt::pick("VOD", &labels)?
+    } else {
+        prompt::select("VOD", vods.len())?
+    };
     let vod = &vods[selection - 1];
-    let url = format!("https://www.twitch.tv/videos/{}", vod.id);
+    let url = vod_url(vod);
+    if args.print_url {
+        println!("{url}");
+        return Ok(());
+    }
     println!("Starting VOD {}...", vod.id);
+    options.title = Some(template::render(
+        streamlink::VIDEO_TITLE_TEMPLATE,
+        &[("display_name", &user.display_name), ("title", &vod.title)],
+    ));
 
-    streamlink::launch(&url).await?;
+    streamlink::launch(&url, &options).await?;
 
     Ok(())
 }
 
-fn prompt_selection(max: usize) -> Result<usize> {
-    loop {
-        print!("Select a VOD (1-{}): ", max);
-        io::stdout().flush().ok();
+#[derive(Serialize)]
+struct JsonVod<'a> {
+    id: &'a str,
+    title: &'a str,
+    created_at: &'a str,
+    duration: String,
+    #[serde(rename = "type")]
+    video_type: &'a str,
+    url: String,
+}
+
+impl<'a> From<&'a TwitchVod> for JsonVod<'a> {
+    fn from(vod: &'a TwitchVod) -> Self {
+        Self {
+            id: &vod.id,
+            title: &vod.title,
+            created_at: &vod.created_at,
+            duration: format_duration(&vod.duration),
+            video_type: &vod.video_type,
+            url: vod_url(vod),
+        }
+    }
+}
 
-        let mut input = String::new();
-        io::stdin()
-            .read_line(&mut input)
-            .context("failed to read input")?;
+fn vod_url(vod: &TwitchVod) -> String {
+    format!("https://www.twitch.tv/videos/{}", vod.id)
+}
 
-        let trimmed = input.trim();
-        if trimmed.is_empty() {
-            bail!("No selection provided.");
+// The title comes last so long titles do not widen other columns.
+fn print_table(out: &mut dyn Write, vods: &[TwitchVod]) -> io::Result<()> {
+    let columns = [
+        ("#", Align::Right),
+        ("created", Align::Left),
+        ("duration", Align::Right),
+        ("type", Align::Left),
+        ("title", Align::Left),
+    ];
+    let rows: Vec<Vec<ColoredString>> = vods
+        .iter()
+        .enumerate()
+        .map(|(idx, vod)| {
+            vec![
+                (idx + 1).to_string().bold(),
+                format_created_at(&vod.created_at).normal(),
+                format_duration(&vod.duration).yellow(),
+                vod.video_type.as_str().dimmed(),
+                vod.title.as_str().normal(),
+            ]
+        })
+        .collect();
+    output::write_table(out, &columns, &rows)
+}
+
+// Twitch durations look like `3h21m33s`, `21m33s`, or `33s`. Anything else is
+// shown as returned.
+fn format_duration(raw: &str) -> String {
+    let mut secs = 0u64;
+    let mut number = String::new();
+    for ch in raw.chars() {
+        if ch.is_ascii_digit() {
+            number.push(ch);
+            continue;
         }
+        let unit = match ch {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return raw.to_string(),
+        };
+        let Some(total) = number
+            .parse::<u64>()
+            .ok()
+            .and_then(|value| value.checked_mul(unit))
+            .and_then(|value| secs.checked_add(value))
+        else {
+            return raw.to_string();
+        };
+        secs = total;
+        number.clear();
+    }
+    if !number.is_empty() || raw.is_empty() {
+        return raw.to_string();
+    }
+    format!(
+        "{:02}:{:02}:{:02}",
+        secs / 3600,
+        (secs % 3600) / 60,
+        secs % 60
+    )
+}
+
+// A plain date covers the whole local day: `--since` starts at its first
+// second and `--until` ends at its last.
+fn parse_date(value: &str, flag: &str, end_of_day: bool) -> Result<DateTime<Utc>> {
+    let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") else {
+        return DateTime::parse_from_rfc3339(value)
+            .map(|at| at.with_timezone(&Utc))
+            .with_context(|| {
+                format!("{flag} must be a YYYY-MM-DD date or an RFC3339 timestamp, got `{value}`")
+            });
+    };
+    let day = if end_of_day {
+        date.checked_add_days(Days::new(1))
+    } else {
+        Some(date)
+    };
+    let start = day
+        .and_then(|day| day.and_hms_opt(0, 0, 0))
+        .and_then(|at| at.and_local_timezone(Local).earliest())
+        .with_context(|| format!("invalid {flag} date `{value}`"))?
+        .with_timezone(&Utc);
+    Ok(if end_of_day {
+        start - chrono::Duration::seconds(1)
+    } else {
+        start
+    })
+}
+
+fn format_created_at(raw: &str) -> String {
+    match DateTime::parse_from_rfc3339(raw) {
+        Ok(at) => at
+            .with_timezone(&Local)
+            .format("%a %b %-d %Y, %H:%M")
+            .to_string(),
+        Err(_) => raw.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_duration_converts_twitch_durations() {
+        assert_eq!(format_duration("3h21m33s"), "03:21:33");
+        assert_eq!(format_duration("21m33s"), "00:21:33");
+        assert_eq!(format_duration("33s"), "00:00:33");
+    }
 
-        match trimmed.parse::<usize>() {
-            Ok(value) if (1..=max).contains(&value) => return Ok(value),
-            _ => println!(
-                "Invalid selection. Please enter a number between 1 and {}.",
-                max
-            ),
+    #[test]
+    fn format_duration_keeps_unparsable_values() {
+        for raw in ["", "3h21", "1d2h", "h", "5124095576030432h"] {
+            assert_eq!(format_duration(raw), raw, "{raw}");
         }
     }
 }
@@ -1,107 +1,961 @@
-use std::collections::HashSet;
-use anyhow::{bail, Context, Result};
+use crate::error::NotFoundError;
+use crate::twitch::TwitchClient;
+use crate::{auth, cache, config, db, fs_utils, login, prompt, streamlink, template};
+use anyhow::{Context, Result, bail};
+use chrono::{SecondsFormat, Utc};
 use clap::Args;
-use crate::streamlink;
+use log::warn;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::io::{self, IsTerminal};
+use std::path::PathBuf;
+use std::process::{Command, ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+// File name of a `--record` recording, rendered with `template::render`.
+const RECORDING_NAME_TEMPLATE: &str = "{login}-{timestamp}.ts";
+
+type StreamHandle = JoinHandle<StreamExit>;
+
+struct StreamExit {
+    record: db::WatchRecord,
+    // Files written with `--record`, one per segment.
+    recordings: Vec<PathBuf>,
+    status: io::Result<ExitStatus>,
+    // Set when Ctrl-C stopped the stream.
+    interrupted: bool,
+}
 
 #[derive(Debug, Args)]
 #[command(about = "Watch Twitch streams via streamlink and mpv")]
 pub struct WatchArgs {
-    #[arg(value_name = "STREAM", required = true, num_args = 1.., help = "Twitch login or URL")]
+    #[arg(
+        value_name = "STREAM",
+        num_args = 1..,
+        help = "Twitch login or URL (omit to choose from the followed streamers that are live)"
+    )]
     pub streams: Vec<String>,
+    #[arg(
+        long,
+        value_parser = streamlink::parse_quality,
+        help = "Stream quality or fallback list like 1080p60,720p,best (defaults to config default_quality, then best)"
+    )]
+    pub quality: Option<String>,
+    #[arg(
+        long,
+        conflicts_with_all = ["quality", "wait"],
+        help = "Choose the quality from the ones each stream offers"
+    )]
+    pub pick_quality: bool,
+    #[arg(
+        long,
+        conflicts_with_all = ["quality", "pick_quality"],
+        help = "Play only the audio (quality audio_only, no mpv window)"
+    )]
+    pub audio_only: bool,
+    #[arg(long, help = "Player executable (defaults to config player, then mpv)")]
+    pub player: Option<String>,
+    #[arg(
+        long,
+        value_name = "NAME",
+        value_parser = non_empty,
+        help = "mpv audio output device (passed as --audio-device; mpv only)"
+    )]
+    pub audio_device: Option<String>,
+    #[arg(
+        long,
+        value_name = "DRIVER",
+        value_parser = non_empty,
+        help = "mpv video output driver (passed as --vo; mpv only)"
+    )]
+    pub video_output: Option<String>,
+    #[arg(
+        long = "extra-arg",
+        value_name = "ARG",
+        allow_hyphen_values = true,
+        help = "Extra streamlink argument for this run (repeatable)"
+    )]
+    pub extra_args: Vec<String>,
+    #[arg(
+        long,
+        help = "Wait for offline streams to go live before starting them"
+    )]
+    pub wait: bool,
+    #[arg(
+        long,
+        value_name = "SECS",
+        default_value_t = 60,
+        requires = "wait",
+        value_parser = clap::value_parser!(u64).range(5..),
+        help = "Seconds between live checks with --wait"
+    )]
+    pub poll_interval: u64,
+    #[arg(
+        long,
+        value_name = "DIR",
+        help = "Record each stream to DIR while it plays"
+    )]
+    pub record: Option<PathBuf>,
+    #[arg(
+        long,
+        requires = "record",
+        conflicts_with_all = ["player", "audio_device", "video_output", "chat"],
+        help = "Only record with --record and never start a player"
+    )]
+    pub record_only: bool,
+    #[arg(
+        long,
+        value_name = "DURATION",
+        requires = "record_only",
+        value_parser = parse_segment,
+        help = "Start a new recording file every DURATION, e.g. 30m, 2h, or 1h30m"
+    )]
+    pub segment: Option<Duration>,
+    #[arg(
+        long,
+        help = "Follow watched streamers that are not followed yet (also settable in config)"
+    )]
+    pub follow_on_watch: bool,
+    #[arg(
+        long,
+        help = "Use streamlink's Twitch low-latency mode and a short player cache (also settable in config)"
+    )]
+    pub low_latency: bool,
+    #[arg(
+        long,
+        help = "Open each stream's chat in the default browser (also settable in config)"
+    )]
+    pub chat: bool,
+    #[arg(
+        long,
+        help = "Fail as soon as any stream fails to start or exits with an error"
+    )]
+    pub strict: bool,
+    #[arg(
+        long,
+        conflicts_with = "streams",
+        help = "Choose the stream with the fuzzy finder even when stdin is not a terminal"
+    )]
+    pub interactive: bool,
 }
 
 pub async fn run(args: WatchArgs) -> Result<()> {
-    streamlink::ensure_dependencies()?;
+    let mut config = config::load_config()?;
+    let streams = if args.streams.is_empty() {
+        vec![pick_online(&mut config, args.interactive).await?]
+    } else {
+        args.streams
+    };
+    if let Some(dir) = &args.record {
+        fs_utils::ensure_dir(dir)?;
+    }
+    let cli_options = streamlink::LaunchOptions {
+        quality: if args.audio_only {
+            Some(streamlink::AUDIO_ONLY_QUALITY.to_string())
+        } else {
+            args.quality
+        },
+        audio_only: args.audio_only,
+        record_only: args.record_only,
+        segment: args.segment,
+        player: args.player,
+        audio_device: args.audio_device,
+        video_output: args.video_output,
+        extra_args: args.extra_args,
+        low_latency: args.low_latency,
+        ..Default::default()
+    };
 
-    let logins = normalize_inputs(&args.streams)?;
-    if logins.is_empty() {
+    if let [input] = streams.as_slice()
+        && let Some(id) = parse_vod_url(input)
+    {
+        if args.wait {
+            bail!("--wait only applies to live streams, not VOD URLs.");
+        }
+        if args.segment.is_some() {
+            bail!("--segment only applies to live streams, not VOD URLs.");
+        }
+        let mut options = vod_options(&cli_options, &config);
+        options.title = Some(template::render(
+            streamlink::VOD_TITLE_TEMPLATE,
+            &[("id", id)],
+        ));
+        if let Some(dir) = &args.record {
+            options.record = Some(dir.join(recording_name(&format!("vod-{id}"))));
+        }
+        streamlink::ensure_dependencies(&options)?;
+        if args.pick_quality {
+            if !io::stdin().is_terminal() {
+                bail!("--pick-quality needs an interactive terminal.");
+            }
+            options.quality = Some(pick_quality(&format!("VOD {id}"), input).await?);
+        }
+        println!("Starting VOD {id}...");
+        if let Some(path) = &options.record {
+            println!("Recording VOD {id} to {}", path.display());
+        }
+        streamlink::launch(input, &options).await?;
+        if let Some(path) = &options.record {
+            println!("VOD {id}: recorded to {}", path.display());
+        }
+        return Ok(());
+    }
+
+    let inputs = normalize_inputs(&streams)?;
+    if inputs.is_empty() {
         bail!("No valid Twitch streams provided.");
     }
 
-    let mut handles = Vec::new();
-    for login in logins {
-        let url = format!("https://www.twitch.tv/{login}");
-        println!("Starting stream for {login}...");
+    let normalized: Vec<&StreamInput> = inputs.iter().filter(|input| input.normalized).collect();
+    if !normalized.is_empty() && !prompt::assume_yes() && io::stdin().is_terminal() {
+        for input in &normalized {
+            println!(
+                "Resolved `{}` to https://www.twitch.tv/{}",
+                input.raw, input.login
+            );
+        }
+        if !prompt::confirm("Start watching?")? {
+            bail!("Aborted.");
+        }
+    }
+
+    let stored_qualities = if cli_options.quality.is_none() {
+        stored_qualities(&inputs).await?
+    } else {
+        HashMap::new()
+    };
+    let mut launches: Vec<(String, streamlink::LaunchOptions)> = inputs
+        .into_iter()
+        .map(|input| {
+            let stored = stored_qualities.get(&input.login).map(String::as_str);
+            let options = resolve_options(&cli_options, &config, &input.login, stored);
+            (input.login, options)
+        })
+        .collect();
+    for (_, options) in &launches {
+        streamlink::ensure_dependencies(options)?;
+    }
+
+    if args.follow_on_watch || config.watch.follow_on_watch {
+        let logins: Vec<String> = launches.iter().map(|(login, _)| login.clone()).collect();
+        if let Err(err) = follow_new(&mut config, &logins).await {
+            warn!("Failed to follow watched streamers: {err:#}");
+        }
+    }
 
-        let mut child = streamlink::spawn(&url)
-            .with_context(|| format!("failed to start streamlink for {login}"))?;
+    if args.pick_quality {
+        if !io::stdin().is_terminal() {
+            bail!("--pick-quality needs an interactive terminal.");
+        }
+        for (login, options) in &mut launches {
+            let url = format!("https://www.twitch.tv/{login}");
+            options.quality = Some(pick_quality(login, &url).await?);
+        }
+    }
 
-        handles.push(tokio::spawn(async move {
-            let status = child.wait().await;
-            (login, status)
-        }));
+    let open_chat = args.chat || config.twitch.open_chat;
+    let mut started = Started::new(args.strict, args.record, open_chat);
+    if args.wait {
+        let interval = Duration::from_secs(args.poll_interval);
+        start_when_live(config, launches, interval, &mut started).await?;
+    } else {
+        for (login, options) in launches {
+            started.start(login, &options)?;
+        }
     }
 
-    let mut failed = Vec::new();
+    let Started {
+        handles,
+        mut failed,
+        ..
+    } = started;
+    // A stream counts as started once streamlink spawned, however it exits.
+    let started = handles.len();
+    let total = started + failed.len();
+    let mut records = Vec::new();
     for handle in handles {
-        let (login, status) = handle.await.context("failed to join stream task")?;
+        let StreamExit {
+            record,
+            recordings,
+            status,
+            interrupted,
+        } = handle.await.context("failed to join stream task")?;
+        let login = &record.login;
+        if !recordings.is_empty() {
+            let paths: Vec<String> = recordings
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect();
+            println!("{login}: recorded to {}", paths.join(", "));
+        }
+        if interrupted {
+            println!("{login}: stopped");
+            records.push(record);
+            continue;
+        }
         match status {
-            Ok(exit) if exit.success() => {}
-            Ok(exit) => failed.push(format!("{login} (exit {exit})")),
-            Err(err) => failed.push(format!("{login} ({err})")),
+            Ok(exit) if exit.success() => {
+                println!("{login}: finished");
+            }
+            Ok(exit) => {
+                println!("{login}: exited with {exit}");
+                failed.push(format!("{login} (exit {exit})"));
+            }
+            Err(err) => {
+                println!("{login}: failed ({err})");
+                failed.push(format!("{login} ({err})"));
+            }
         }
+        records.push(record);
+    }
+
+    if let Err(err) = record_history(&records).await {
+        warn!("Failed to record watch history: {err:#}");
     }
 
-    if !failed.is_empty() {
+    if failed.is_empty() {
+        return Ok(());
+    }
+    if started == 0 {
+        bail!("No streams could be started: {}", failed.join(", "));
+    }
+    if args.strict {
         bail!(
             "Some streams failed to start or exited early: {}",
             failed.join(", ")
         );
     }
+    warn!(
+        "{} of {} stream(s) failed: {}",
+        failed.len(),
+        total,
+        failed.join(", ")
+    );
+
+    Ok(())
+}
+
+// Offers the followed streamers that are live, most viewers first, and
+// returns the login of the chosen one. Without a terminal (and without
+// `--interactive`) they are listed for the numeric prompt instead.
+async fn pick_online(config: &mut config::Config, interactive: bool) -> Result<String> {
+    if config::token_needs_refresh(config) {
+        auth::run(auth::AuthArgs::default()).await?;
+        *config = config::load_config()?;
+    }
+    let client = TwitchClient::from_config(config)?;
+
+    let pool = db::connect().await?;
+    let ids: Vec<String> = db::list_streamers(&pool)
+        .await?
+        .into_iter()
+        .map(|streamer| streamer.id)
+        .collect();
+    if ids.is_empty() {
+        bail!("You are not following anyone yet. Run `ttv follow <LOGIN>` or pass a login.");
+    }
+    let mut streams = client.fetch_streams_by_user_ids(&ids).await?;
+    if streams.is_empty() {
+        bail!("None of the followed streamers are live.");
+    }
+    streams.sort_by_key(|stream| Reverse(stream.viewer_count));
+
+    let labels: Vec<String> = streams
+        .iter()
+        .map(|stream| {
+            format!(
+                "{} - {} ({} viewers): {}",
+                stream.user_login, stream.game_name, stream.viewer_count, stream.title
+            )
+        })
+        .collect();
+    let selection = if interactive || io::stdin().is_terminal() {
+        prompt::pick("streamer", &labels)?
+    } else {
+        println!("Live followed streamers:");
+        for (idx, label) in labels.iter().enumerate() {
+            println!("{:>2}) {}", idx + 1, label);
+        }
+        prompt::select("streamer", labels.len())?
+    };
+    Ok(streams.swap_remove(selection - 1).user_login)
+}
+
+// Precedence: CLI flags, then the quality stored with `ttv quality`, then the
+// streamer's launch override, then the config defaults, then the built-in
+// defaults in `streamlink`.
+fn resolve_options(
+    cli: &streamlink::LaunchOptions,
+    config: &config::Config,
+    login: &str,
+    stored_quality: Option<&str>,
+) -> streamlink::LaunchOptions {
+    let launch = config.launch_override(login);
+    let mut options = cli.clone();
+    options.quality = cli
+        .quality
+        .clone()
+        .or_else(|| stored_quality.map(str::to_string))
+        .or_else(|| launch.and_then(|launch| launch.quality.clone()))
+        .or_else(|| config.player.default_quality.clone());
+    options.player = cli
+        .player
+        .clone()
+        .or_else(|| launch.and_then(|launch| launch.player.clone()))
+        .or_else(|| config.player.player.clone());
+    options.player_args = config.player.player_args.clone();
+    options.low_latency = cli.low_latency || config.twitch.low_latency;
+    // Extra arguments add up: config, then the streamer's, then this run's.
+    options.extra_args = config.player.streamlink_args.clone();
+    if let Some(launch) = launch {
+        options.extra_args.extend(launch.extra_args.iter().cloned());
+    }
+    options.extra_args.extend(cli.extra_args.iter().cloned());
+    options.title = Some(template::render(
+        streamlink::STREAM_TITLE_TEMPLATE,
+        &[("login", login)],
+    ));
+    options
+}
 
+// VODs have no login, so only the CLI flags and the config defaults apply.
+// Low latency is a live-only streamlink option.
+fn vod_options(
+    cli: &streamlink::LaunchOptions,
+    config: &config::Config,
+) -> streamlink::LaunchOptions {
+    let mut options = cli.clone();
+    options.quality = cli
+        .quality
+        .clone()
+        .or_else(|| config.player.default_quality.clone());
+    options.player = cli.player.clone().or_else(|| config.player.player.clone());
+    options.player_args = config.player.player_args.clone();
+    options.low_latency = false;
+    options.extra_args = config.player.streamlink_args.clone();
+    options.extra_args.extend(cli.extra_args.iter().cloned());
+    options
+}
+
+// Qualities set with `ttv quality`, keyed by login. Streamers that are not
+// followed have none.
+async fn stored_qualities(inputs: &[StreamInput]) -> Result<HashMap<String, String>> {
+    let pool = db::connect().await?;
+    let mut qualities = HashMap::new();
+    for input in inputs {
+        if let Some(quality) = db::preferred_quality(&pool, &input.login).await? {
+            qualities.insert(input.login.clone(), quality);
+        }
+    }
+    Ok(qualities)
+}
+
+// Adds the logins that are not followed yet to the local follows.
+async fn follow_new(config: &mut config::Config, logins: &[String]) -> Result<()> {
+    let pool = db::connect().await?;
+    let followed: HashSet<String> = db::list_streamers(&pool)
+        .await?
+        .into_iter()
+        .map(|streamer| streamer.name.to_lowercase())
+        .collect();
+    let new: Vec<String> = logins
+        .iter()
+        .filter(|login| !followed.contains(*login))
+        .cloned()
+        .collect();
+    if new.is_empty() {
+        return Ok(());
+    }
+
+    if config::token_needs_refresh(config) {
+        auth::run(auth::AuthArgs::default()).await?;
+        *config = config::load_config()?;
+    }
+    let client = TwitchClient::from_config(config)?;
+
+    let users = cache::users_by_login(&pool, config, &client, &new).await?;
+    for user in &users {
+        db::upsert_streamer(&pool, user).await?;
+        println!("Followed {} ({}).", user.login, user.display_name);
+    }
     Ok(())
 }
 
-fn normalize_inputs(inputs: &[String]) -> Result<Vec<String>> {
+// Queries streamlink once per stream; the chosen name is then passed through
+// as the quality, so launching does not need to ask again.
+async fn pick_quality(name: &str, url: &str) -> Result<String> {
+    let mut qualities = streamlink::qualities(url).await?;
+    if qualities.is_empty() {
+        bail!("No qualities available for {name}.");
+    }
+
+    println!("Qualities for {name}:");
+    for (idx, quality) in qualities.iter().enumerate() {
+        println!("{:>2}) {}", idx + 1, quality);
+    }
+    let selection = prompt::select("quality", qualities.len())?;
+    Ok(qualities.swap_remove(selection - 1))
+}
+
+// Tracks launched streams. Unless `strict` is set, a stream that cannot be
+// started is recorded as failed and the remaining ones are still launched.
+struct Started {
+    strict: bool,
+    record_dir: Option<PathBuf>,
+    open_chat: bool,
+    handles: Vec<StreamHandle>,
+    failed: Vec<String>,
+}
+
+impl Started {
+    fn new(strict: bool, record_dir: Option<PathBuf>, open_chat: bool) -> Self {
+        Self {
+            strict,
+            record_dir,
+            open_chat,
+            handles: Vec::new(),
+            failed: Vec::new(),
+        }
+    }
+
+    fn start(&mut self, login: String, options: &streamlink::LaunchOptions) -> Result<()> {
+        match start_stream(login.clone(), options, self.record_dir.clone()) {
+            Ok(handle) => {
+                self.handles.push(handle);
+                if self.open_chat {
+                    open_chat(&login);
+                }
+            }
+            Err(err) if !self.strict => {
+                warn!("{err:#}");
+                self.failed.push(format!("{login} ({err:#})"));
+            }
+            Err(err) => return Err(err),
+        }
+        Ok(())
+    }
+}
+
+// Chat opens through the platform's URL handler. A failure only warns; the
+// stream keeps playing.
+fn open_chat(login: &str) {
+    let url = format!("https://www.twitch.tv/{login}/chat");
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else {
+        Command::new("xdg-open")
+    };
+    let result = command
+        .arg(&url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+    if !result.is_ok_and(|status| status.success()) {
+        warn!("Could not open the chat for {login}; open {url} manually.");
+    }
+}
+
+fn recording_name(login: &str) -> String {
+    let timestamp = Utc::now().format("%Y%m%d-%H%M%S").to_string();
+    template::render(
+        RECORDING_NAME_TEMPLATE,
+        &[("login", login), ("timestamp", &timestamp)],
+    )
+}
+
+// With a segment length, streamlink stops after each segment and is started
+// again with a new file until the stream ends or Ctrl-C is pressed.
+fn start_stream(
+    login: String,
+    options: &streamlink::LaunchOptions,
+    record_dir: Option<PathBuf>,
+) -> Result<StreamHandle> {
+    let url = format!("https://www.twitch.tv/{login}");
+    println!("Starting stream for {login}...");
+    let mut options = options.clone();
+    options.record = record_dir
+        .as_ref()
+        .map(|dir| dir.join(recording_name(&login)));
+    if let Some(path) = &options.record {
+        println!("Recording {login} to {}", path.display());
+    }
+
+    let started_at = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let start = Instant::now();
+    let mut child = streamlink::spawn(&url, &options)
+        .with_context(|| format!("failed to start streamlink for {login}"))?;
+
+    // Stop streamlink and its player on Ctrl-C so that no player is left
+    // behind once ttv exits.
+    Ok(tokio::spawn(async move {
+        let mut recordings: Vec<PathBuf> = options.record.iter().cloned().collect();
+        loop {
+            let segment_start = Instant::now();
+            let (status, interrupted) = tokio::select! {
+                status = child.wait() => (status, false),
+                _ = tokio::signal::ctrl_c() => {
+                    let status = streamlink::stop(&mut child).await;
+                    if let Err(err) = &status {
+                        warn!("Failed to stop streamlink for {login}: {err}");
+                    }
+                    (status, true)
+                }
+            };
+            // A run that ends before the segment is over means the stream ended.
+            let segment_done = options.segment.is_some_and(|segment| {
+                !interrupted
+                    && status.as_ref().is_ok_and(|status| status.success())
+                    && segment_start.elapsed() >= segment
+            });
+            if let (true, Some(dir)) = (segment_done, &record_dir) {
+                let path = dir.join(recording_name(&login));
+                options.record = Some(path.clone());
+                match streamlink::spawn(&url, &options) {
+                    Ok(next) => {
+                        println!("Recording {login} to {}", path.display());
+                        recordings.push(path);
+                        child = next;
+                        continue;
+                    }
+                    Err(err) => warn!("Could not start the next segment for {login}: {err:#}"),
+                }
+            }
+            // streamlink only creates the file once data arrives.
+            recordings.retain(|path| path.exists());
+            break StreamExit {
+                record: db::WatchRecord {
+                    login,
+                    url,
+                    started_at,
+                    duration_secs: start.elapsed().as_secs() as i64,
+                },
+                recordings,
+                status,
+                interrupted,
+            };
+        }
+    }))
+}
+
+async fn record_history(records: &[db::WatchRecord]) -> Result<()> {
+    if records.is_empty() {
+        return Ok(());
+    }
+
+    let pool = db::connect().await?;
+    for record in records {
+        db::insert_watch_record(&pool, record).await?;
+    }
+    Ok(())
+}
+
+// Polls until every channel is live, starting each one as soon as it is.
+// Ctrl-C stops waiting for the remaining channels.
+async fn start_when_live(
+    mut config: config::Config,
+    launches: Vec<(String, streamlink::LaunchOptions)>,
+    interval: Duration,
+    started: &mut Started,
+) -> Result<()> {
+    if config::token_needs_refresh(&config) {
+        auth::run(auth::AuthArgs::default()).await?;
+        config = config::load_config()?;
+    }
+
+    let client = TwitchClient::from_config(&config)?;
+
+    let logins: Vec<String> = launches.iter().map(|(login, _)| login.clone()).collect();
+    let mut options_by_login: HashMap<String, streamlink::LaunchOptions> =
+        launches.into_iter().collect();
+    let pool = db::connect().await?;
+    let users = cache::users_by_login(&pool, &config, &client, &logins).await?;
+    let mut pending: HashMap<String, String> = users
+        .into_iter()
+        .map(|user| (user.id, user.login.to_lowercase()))
+        .collect();
+    let found: HashSet<&String> = pending.values().collect();
+    let missing: Vec<&str> = logins
+        .iter()
+        .filter(|login| !found.contains(login))
+        .map(String::as_str)
+        .collect();
+    if !missing.is_empty() {
+        eprintln!("Not found on Twitch: {}", missing.join(", "));
+    }
+    if pending.is_empty() {
+        return Err(NotFoundError::error(
+            "No streamers found for the provided login names.",
+        ));
+    }
+
+    while !pending.is_empty() {
+        let ids: Vec<String> = pending.keys().cloned().collect();
+        let streams = client.fetch_streams_by_user_ids(&ids).await?;
+        for stream in streams {
+            if let Some(login) = pending.remove(&stream.user_id) {
+                let options = options_by_login.remove(&login).unwrap_or_default();
+                started.start(login, &options)?;
+            }
+        }
+        if pending.is_empty() {
+            break;
+        }
+
+        let mut waiting: Vec<&str> = pending.values().map(String::as_str).collect();
+        waiting.sort_unstable();
+        println!(
+            "Waiting for {} to go live (checking every {}s)...",
+            waiting.join(", "),
+            interval.as_secs()
+        );
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            _ = tokio::signal::ctrl_c() => {
+                println!("Stopped waiting.");
+                break;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Accepts hours, minutes, and seconds like `2h`, `30m`, or `1h30m`.
+fn parse_segment(value: &str) -> Result<Duration, String> {
+    let invalid = || format!("expected a duration like 30m, 2h, or 1h30m, got `{value}`");
+    let mut secs: u64 = 0;
+    let mut digits = String::new();
+    for ch in value.trim().chars() {
+        if ch.is_ascii_digit() {
+            digits.push(ch);
+            continue;
+        }
+        let unit = match ch {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return Err(invalid()),
+        };
+        secs = digits
+            .parse::<u64>()
+            .ok()
+            .and_then(|amount| amount.checked_mul(unit))
+            .and_then(|amount| secs.checked_add(amount))
+            .ok_or_else(invalid)?;
+        digits.clear();
+    }
+    if !digits.is_empty() || secs == 0 {
+        return Err(invalid());
+    }
+    if secs < 60 {
+        return Err("segments must be at least one minute long".to_string());
+    }
+    Ok(Duration::from_secs(secs))
+}
+
+fn non_empty(value: &str) -> Result<String, String> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        return Err("value must not be empty".to_string());
+    }
+    Ok(trimmed.to_string())
+}
+
+struct StreamInput {
+    raw: String,
+    login: String,
+    // True when the input needed more than trimming a canonical URL down to
+    // the login, e.g. a leading `@` or an `m.twitch.tv` URL.
+    normalized: bool,
+}
+
+fn normalize_inputs(inputs: &[String]) -> Result<Vec<StreamInput>> {
     let mut seen = HashSet::new();
-    let mut logins = Vec::new();
+    let mut resolved = Vec::new();
 
     for input in inputs {
-        let login = parse_login(input)?;
+        let (login, normalized) = parse_login(input)?;
         let key = login.to_lowercase();
         if seen.insert(key.clone()) {
-            logins.push(key);
+            resolved.push(StreamInput {
+                raw: input.clone(),
+                login: key,
+                normalized,
+            });
         }
     }
 
-    Ok(logins)
+    Ok(resolved)
 }
 
-fn parse_login(input: &str) -> Result<String> {
+fn parse_login(input: &str) -> Result<(String, bool)> {
     if let Some(login) = parse_twitch_url(input) {
         return Ok(login);
     }
 
-    if is_valid_login(input) {
-        return Ok(input.to_string());
+    if login::is_valid_login(input) {
+        return Ok((input.to_string(), false));
+    }
+
+    if let Some(login) = input.strip_prefix('@')
+        && login::is_valid_login(login)
+    {
+        return Ok((login.to_string(), true));
+    }
+
+    if parse_vod_url(input).is_some() {
+        bail!("VOD URLs can only be watched on their own: {input}");
     }
 
     bail!("Invalid Twitch URL or login: {input}")
 }
 
-fn parse_twitch_url(input: &str) -> Option<String> {
-    let without_scheme = input
-        .strip_prefix("https://")
-        .or_else(|| input.strip_prefix("http://"))?;
-    let without_www = without_scheme
-        .strip_prefix("www.")
-        .unwrap_or(without_scheme);
-    let path = without_www.strip_prefix("twitch.tv/")?;
+fn parse_twitch_url(input: &str) -> Option<(String, bool)> {
+    let (path, normalized) = twitch_path(input)?;
 
-    if path.is_empty() || path.contains('/') || path.contains('?') || path.contains('#') {
+    // Multi-segment paths such as `/videos/123` are not channel pages; see
+    // `parse_vod_url` for those.
+    if path.is_empty() || path.contains('/') {
         return None;
     }
 
-    if !is_valid_login(path) {
+    if !login::is_valid_login(path) {
         return None;
     }
 
-    Some(path.to_string())
+    Some((path.to_string(), normalized))
+}
+
+// Returns the video ID of a `twitch.tv/videos/<id>` URL.
+fn parse_vod_url(input: &str) -> Option<&str> {
+    let (path, _) = twitch_path(input)?;
+    let id = path.strip_prefix("videos/")?;
+    (!id.is_empty() && id.chars().all(|ch| ch.is_ascii_digit())).then_some(id)
 }
 
-fn is_valid_login(login: &str) -> bool {
-    !login.is_empty()
-        && login
-            .chars()
-            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+// The path of a twitch.tv URL without the leading and trailing `/`, and
+// whether anything beyond the scheme and `www.` had to be stripped to get it.
+fn twitch_path(input: &str) -> Option<(&str, bool)> {
+    let without_scheme = input
+        .strip_prefix("https://")
+        .or_else(|| input.strip_prefix("http://"))?;
+    let (host, mobile) = match without_scheme.strip_prefix("m.") {
+        Some(host) => (host, true),
+        None => (
+            without_scheme
+                .strip_prefix("www.")
+                .unwrap_or(without_scheme),
+            false,
+        ),
+    };
+    let path = host.strip_prefix("twitch.tv/")?;
+    // Query strings like `?referrer=raid` and fragments never name a channel.
+    let (path, extra) = match path.find(['?', '#']) {
+        Some(end) => (&path[..end], true),
+        None => (path, false),
+    };
+    let (path, slash) = match path.strip_suffix('/') {
+        Some(path) => (path, true),
+        None => (path, false),
+    };
+    Some((path, mobile || extra || slash))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn channel(input: &str) -> Option<String> {
+        parse_twitch_url(input).map(|(login, _)| login)
+    }
+
+    #[test]
+    fn twitch_path_strips_host_and_slashes() {
+        assert_eq!(
+            twitch_path("https://www.twitch.tv/jonhoo"),
+            Some(("jonhoo", false))
+        );
+        assert_eq!(
+            twitch_path("https://m.twitch.tv/jonhoo/"),
+            Some(("jonhoo", true))
+        );
+        assert_eq!(
+            twitch_path("http://twitch.tv/videos/123"),
+            Some(("videos/123", false))
+        );
+        assert_eq!(twitch_path("https://example.com/jonhoo"), None);
+        assert_eq!(twitch_path("twitch.tv/jonhoo"), None);
+    }
+
+    #[test]
+    fn parse_twitch_url_accepts_channel_urls() {
+        for input in ["https://twitch.tv/jonhoo", "https://www.twitch.tv/jonhoo"] {
+            assert_eq!(
+                parse_twitch_url(input),
+                Some(("jonhoo".to_string(), false)),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_twitch_url_flags_normalized_urls() {
+        for input in [
+            "https://m.twitch.tv/jonhoo",
+            "https://www.twitch.tv/jonhoo/",
+            "https://www.twitch.tv/jonhoo?referrer=raid",
+            "https://www.twitch.tv/jonhoo#chat",
+            "https://m.twitch.tv/jonhoo?referrer=raid",
+            "http://m.twitch.tv/jonhoo/?referrer=raid#chat",
+        ] {
+            assert_eq!(
+                parse_twitch_url(input),
+                Some(("jonhoo".to_string(), true)),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_twitch_url_rejects_non_channel_paths() {
+        assert_eq!(channel("https://www.twitch.tv/videos/123"), None);
+        assert_eq!(channel("https://www.twitch.tv/"), None);
+        assert_eq!(channel("https://www.twitch.tv/?referrer=raid"), None);
+        assert_eq!(channel("https://www.twitch.tv/not-a-login"), None);
+    }
+
+    #[test]
+    fn parse_vod_url_accepts_video_ids() {
+        assert_eq!(
+            parse_vod_url("https://www.twitch.tv/videos/123"),
+            Some("123")
+        );
+        assert_eq!(
+            parse_vod_url("https://m.twitch.tv/videos/123/"),
+            Some("123")
+        );
+        assert_eq!(
+            parse_vod_url("https://twitch.tv/videos/123?t=1h2m"),
+            Some("123")
+        );
+        assert_eq!(parse_vod_url("https://twitch.tv/videos/abc"), None);
+        assert_eq!(parse_vod_url("https://twitch.tv/videos/"), None);
+        assert_eq!(parse_vod_url("https://twitch.tv/jonhoo"), None);
+    }
+
+    #[test]
+    fn recording_name_has_login_and_timestamp() {
+        let name = recording_name("jonhoo");
+        let timestamp = name
+            .strip_prefix("jonhoo-")
+            .and_then(|rest| rest.strip_suffix(".ts"))
+            .unwrap();
+        assert!(chrono::NaiveDateTime::parse_from_str(timestamp, "%Y%m%d-%H%M%S").is_ok());
+    }
 }